@@ -5,11 +5,12 @@ use modules::system::AppSys;
 // Import all commands from modules
 use modules::chat::*;
 use modules::database::*;
+use modules::models::*;
 use modules::settings::*;
 use modules::system::*;
 
 // Import Tauri window builder components
-use tauri::{TitleBarStyle, WebviewUrl, WebviewWindowBuilder};
+use tauri::{Manager, TitleBarStyle, WebviewUrl, WebviewWindowBuilder};
 
 // Import window vibrancy for macOS blur effects
 #[cfg(target_os = "macos")]
@@ -29,11 +30,12 @@ pub fn run() {
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
         .manage(AppSys::default())
+        .manage(ActiveGenerations::default())
+        .manage(ActivePulls::default())
         .invoke_handler(tauri::generate_handler![
             save_settings,
             load_settings,
             has_settings,
-            get_api_key,
             set_api_key,
             get_provider_config,
             get_hardware_info,
@@ -53,18 +55,35 @@ pub fn run() {
             db_get_ai_messages,
             db_add_ai_message,
             db_save_complete_message,
+            db_search,
             stream_ollama_chat,
+            stream_provider_chat,
+            complete_provider_chat,
+            cancel_generation,
+            pull_ollama_model,
+            cancel_ollama_pull,
+            delete_ollama_model,
+            copy_ollama_model,
+            create_ollama_model,
+            list_running_ollama_models,
             list_chat_models,
+            refresh_chat_models,
             get_adapter_models,
+            check_provider_health,
+            get_ollama_model_info,
             db_delete_conversation,
             db_archive_conversation,
             db_update_conversation_title,
             db_update_conversation_model,
             db_get_conversation,
+            db_get_conversation_options,
+            db_update_conversation_options,
             #[cfg(target_os = "macos")]
             apply_vibrancy_effect
         ])
         .setup(|app| {
+            app.manage(Database::open(db_path(app.handle())?)?);
+
             let mut win_builder =
                 WebviewWindowBuilder::new(app, "main", WebviewUrl::default())
                     .title("Tethra")
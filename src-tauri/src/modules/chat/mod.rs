@@ -1,10 +1,134 @@
-use crate::modules::providers::OllamaProvider;
-use crate::modules::settings::read_settings;
+use crate::modules::database::{
+    db_save_complete_message, read_conversation_options, SaveCompleteMessageInput,
+};
+use crate::modules::error::{AppError, AppResult, ErrorKind};
+use crate::modules::providers::{
+    resolve_capabilities, ChatEvent, ChatRequest, GenerationOptions, ModelCache, ModelCapabilities,
+    ModelDetails, ModelInfo, OllamaProvider, Provider, ProviderHealth, ProviderRegistry, ToolCall,
+    ToolDefinition, Usage,
+};
+use crate::modules::settings::{read_settings, settings_path};
+use crate::modules::utils::uuid;
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{Emitter, Manager};
+
+/// Generations currently streaming, keyed by assistant message id.
+#[derive(Default)]
+pub struct ActiveGenerations(pub Mutex<HashMap<String, ActiveGeneration>>);
+
+pub struct ActiveGeneration {
+    pub conversation_id: String,
+    pub reply: Arc<Mutex<GeneratedReply>>,
+    pub task: tauri::async_runtime::JoinHandle<()>,
+}
+
+/// Everything a generation has produced so far, so a cancelled reply keeps it all.
+#[derive(Debug, Default)]
+pub struct GeneratedReply {
+    pub reasoning: Vec<ReasoningBlock>,
+    pub content: String,
+    pub tool_calls: Vec<ToolCall>,
+    pub usage: Option<Usage>,
+    reasoning_open: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReasoningBlock {
+    pub text: String,
+    pub signature: Option<String>,
+}
+
+impl GeneratedReply {
+    /// Add reasoning to the open block, starting one after text or a signed block.
+    fn push_reasoning(&mut self, token: &str) {
+        match self.reasoning.last_mut() {
+            Some(block) if self.reasoning_open => block.text.push_str(token),
+            _ => {
+                self.reasoning.push(ReasoningBlock {
+                    text: token.to_string(),
+                    signature: None,
+                });
+                self.reasoning_open = true;
+            }
+        }
+    }
+
+    /// A signature closes its thinking block.
+    fn sign_reasoning(&mut self, signature: String) {
+        if !self.reasoning_open {
+            self.push_reasoning("");
+        }
+        if let Some(block) = self.reasoning.last_mut() {
+            block.signature = Some(signature);
+        }
+        self.reasoning_open = false;
+    }
+
+    fn push_text(&mut self, token: &str) {
+        self.reasoning_open = false;
+        self.content.push_str(token);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.reasoning.is_empty() && self.content.is_empty() && self.tool_calls.is_empty()
+    }
+
+    /// The reply as an AI SDK UI message, in the shape the webview saves a finished one.
+    fn to_ui_message(&self, id: &str) -> serde_json::Value {
+        let mut parts = vec![serde_json::json!({ "type": "step-start" })];
+        for block in &self.reasoning {
+            let mut part = serde_json::json!({
+                "type": "reasoning",
+                "text": block.text,
+                "state": "done"
+            });
+            if let Some(signature) = &block.signature {
+                part["providerMetadata"] =
+                    serde_json::json!({ "anthropic": { "signature": signature } });
+            }
+            parts.push(part);
+        }
+        if !self.content.is_empty() {
+            parts.push(serde_json::json!({
+                "type": "text",
+                "text": self.content,
+                "state": "done"
+            }));
+        }
+        for call in &self.tool_calls {
+            parts.push(serde_json::json!({
+                "type": format!("tool-{}", call.name),
+                "toolCallId": call.id,
+                "state": "input-available",
+                "input": call.arguments
+            }));
+        }
+
+        let mut message = serde_json::json!({
+            "id": id,
+            "role": "assistant",
+            "parts": parts
+        });
+        if let Some(usage) = &self.usage {
+            message["metadata"] = serde_json::json!({
+                "usage": {
+                    "inputTokens": usage.input_tokens,
+                    "outputTokens": usage.output_tokens,
+                    "totalTokens": usage.input_tokens + usage.output_tokens
+                }
+            });
+        }
+        message
+    }
+}
 
 #[derive(Debug, Clone, Serialize)]
 pub struct ChatStreamToken {
     pub conversation_id: String,
+    pub message_id: String,
     pub token: String,
 }
 
@@ -19,6 +143,38 @@ pub struct ChatStreamEnd {
     pub conversation_id: String,
     pub message_id: String,
     pub complete_content: String,
+    pub tool_calls: Vec<ToolCall>, // Calls the caller should run and answer with tool results
+    pub usage: Option<Usage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatStreamReasoning {
+    pub conversation_id: String,
+    pub message_id: String,
+    pub token: String,
+    pub signature: Option<String>, // Sent once at the end of a signed thinking block
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatStreamToolCall {
+    pub conversation_id: String,
+    pub message_id: String,
+    pub tool_call: ToolCall,
+}
+
+/// Sent with `chat_stream_cancelled`: the reply so far and a `cancelled` error.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatStreamCancelled {
+    #[serde(flatten)]
+    pub end: ChatStreamEnd,
+    pub error: AppError,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatStreamError {
+    pub conversation_id: String,
+    pub message_id: String,
+    pub error: AppError,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -33,6 +189,15 @@ pub struct ListedModel {
     pub model: String,
     pub adapter_kind: String,
     pub enabled: bool,
+    pub capabilities: ModelCapabilities,
+    pub error: Option<AppError>, // Why the provider's models could not be fetched
+}
+
+/// Models to pick from, and why providers that list none were left out.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatModels {
+    pub models: Vec<ListedModel>,
+    pub unavailable: Vec<AppError>, // One per provider, named in `provider`
 }
 
 // stream_chat is no longer used - frontend uses AI SDK directly
@@ -383,374 +548,564 @@ pub async fn stream_chat(app: tauri::AppHandle, input: StreamChatInput) -> Resul
 }
 */
 
+/// Which providers to ask again instead of using their cached model list.
+pub enum ModelRefresh {
+    Cached,
+    All,
+    Only(String), // Adapter kind
+}
+
 #[tauri::command]
-pub async fn list_chat_models(app: tauri::AppHandle) -> Result<Vec<ListedModel>, String> {
-    // Build from settings, not env, and only providers we support
-    // Keep OpenAI, Anthropic, Gemini, OpenRouter, Groq; exclude Cohere/Mistral/Llama.cpp
-    let settings = read_settings(&app).unwrap_or_default();
-    let mut out = Vec::new();
+pub async fn list_chat_models(app: tauri::AppHandle) -> AppResult<ChatModels> {
+    discover_chat_models(&app, ModelRefresh::Cached).await
+}
 
-    // OpenAI - enabled if API key is set
-    let openai_has_api_key = settings.openai_api_key.is_some() || settings.api_key.is_some();
-
-    if openai_has_api_key {
-        // Add manually configured models first
-        if let Some(models) = settings.openai_models.clone() {
-            for m in models {
-                out.push(ListedModel {
-                    model: m,
-                    adapter_kind: "OpenAI".to_string(),
-                    enabled: true,
-                });
-            }
-        }
+/// Ask providers for their models again, ignoring the cache; every provider when
+/// `adapter_kind` is not given.
+#[tauri::command]
+pub async fn refresh_chat_models(
+    app: tauri::AppHandle,
+    adapter_kind: Option<String>,
+) -> AppResult<ChatModels> {
+    let refresh = match adapter_kind {
+        Some(kind) => ModelRefresh::Only(kind),
+        None => ModelRefresh::All,
+    };
+    discover_chat_models(&app, refresh).await
+}
 
-        // If no manual models configured, use default models
-        if settings.openai_models.is_none() {
-            // Show popular default models
-            let default_models = vec!["gpt-4o", "gpt-4o-mini", "gpt-4-turbo", "gpt-3.5-turbo"];
-            for model in default_models {
-                out.push(ListedModel {
-                    model: model.to_string(),
-                    adapter_kind: "OpenAI".to_string(),
-                    enabled: true,
-                });
-            }
-        }
+/// List the models of every configured provider.
+///
+/// Models picked in settings win. Otherwise the cached list is used while it is fresh,
+/// and providers are queried live (concurrently) when it is stale or a refresh was
+/// asked for. A provider that cannot be reached serves its last known list, disabled and
+/// carrying the error. Providers that were never reached, or whose settings are unusable,
+/// list no models and are reported in `unavailable`.
+pub async fn discover_chat_models(
+    app: &tauri::AppHandle,
+    refresh: ModelRefresh,
+) -> AppResult<ChatModels> {
+    // Build from settings, not env; every provider comes from the registry
+    let settings = read_settings(app).unwrap_or_default();
+    let registry = ProviderRegistry::from_settings(&settings);
+    let cache_path = settings_path(app)?.with_file_name("model_cache.json");
+    let mut cache = ModelCache::load(&cache_path);
+
+    // Providers without an API key are hidden until one is set
+    let entries: Vec<_> = registry
+        .entries()
+        .iter()
+        .filter(|entry| entry.provider.is_configured())
+        .collect();
+
+    match &refresh {
+        ModelRefresh::Cached => {}
+        ModelRefresh::All => cache.invalidate_all(),
+        ModelRefresh::Only(kind) => cache.invalidate(kind),
     }
+    let to_fetch: Vec<_> = entries
+        .iter()
+        .filter(|entry| {
+            entry.models.is_none()
+                && !cache.is_fresh(entry.provider.kind(), entry.provider.base_url())
+        })
+        .map(|entry| entry.provider.clone())
+        .collect();
+    let fetched = futures::future::join_all(to_fetch.iter().map(|provider| async move {
+        (
+            provider.kind().to_string(),
+            provider.list_model_info().await,
+        )
+    }))
+    .await;
+    let mut fetched: HashMap<String, AppResult<Vec<ModelInfo>>> = fetched.into_iter().collect();
 
-    // Anthropic - enabled if API key is set
-    let anthropic_has_api_key = settings.anthropic_api_key.is_some();
-
-    if anthropic_has_api_key {
-        // Add manually configured models first
-        if let Some(models) = settings.anthropic_models.clone() {
-            for m in models {
-                out.push(ListedModel {
-                    model: m,
-                    adapter_kind: "Anthropic".to_string(),
-                    enabled: true,
-                });
+    let mut out = Vec::new();
+    let mut unavailable = registry.skipped().to_vec();
+    let mut cache_changed = false;
+    for entry in entries {
+        let provider = &entry.provider;
+        let kind = provider.kind();
+        let mut error = None;
+        let (models, enabled) = if let Some(models) = &entry.models {
+            // Picked models keep what discovery learned about them
+            let known = cache
+                .get(kind, provider.base_url())
+                .map(|cached| cached.models.as_slice())
+                .unwrap_or_default();
+            let models = models
+                .iter()
+                .map(|name| {
+                    known
+                        .iter()
+                        .find(|info| &info.name == name)
+                        .cloned()
+                        .unwrap_or_else(|| ModelInfo::named(name.clone()))
+                })
+                .collect();
+            (models, true)
+        } else if let Some(result) = fetched.remove(kind) {
+            cache_changed |= result.is_ok();
+            let listing = cache.record(kind, provider.base_url(), result);
+            match listing.error {
+                None => (listing.models, true),
+                Some(mut e) if listing.models.is_empty() => {
+                    // Never reached, so there is nothing to offer
+                    e.provider.get_or_insert_with(|| kind.to_string());
+                    unavailable.push(e);
+                    continue;
+                }
+                Some(e) => {
+                    error = Some(e);
+                    (listing.models, false)
+                }
             }
+        } else {
+            let cached = cache.get(kind, provider.base_url());
+            (cached.map(|c| c.models.clone()).unwrap_or_default(), true)
+        };
+        for info in models {
+            out.push(ListedModel {
+                capabilities: resolve_capabilities(
+                    &info.name,
+                    &info.capabilities,
+                    settings.model_capabilities.as_ref(),
+                ),
+                model: info.name,
+                adapter_kind: kind.to_string(),
+                enabled,
+                error: error.clone(),
+            });
         }
+    }
 
-        // If no manual models configured, use default models
-        if settings.anthropic_models.is_none() {
-            // Show popular default models
-            let default_models = vec![
-                "claude-3-5-sonnet-20241022",
-                "claude-3-5-haiku-20241022",
-                "claude-3-opus-20240229",
-                "claude-3-sonnet-20240229",
-                "claude-3-haiku-20240307",
-            ];
-            for model in default_models {
-                out.push(ListedModel {
-                    model: model.to_string(),
-                    adapter_kind: "Anthropic".to_string(),
-                    enabled: true,
-                });
-            }
+    if cache_changed {
+        if let Err(e) = cache.save(&cache_path) {
+            log::warn!("Failed to save model cache: {}", e);
         }
     }
 
-    // Gemini - enabled if API key is set
-    let gemini_has_api_key = settings.gemini_api_key.is_some();
-
-    if gemini_has_api_key {
-        // Add manually configured models first
-        if let Some(models) = settings.gemini_models.clone() {
-            for m in models {
-                out.push(ListedModel {
-                    model: m,
-                    adapter_kind: "Gemini".to_string(),
-                    enabled: true,
-                });
-            }
-        }
+    Ok(ChatModels {
+        models: out,
+        unavailable,
+    })
+}
 
-        // If no manual models configured, use default models
-        if settings.gemini_models.is_none() {
-            // Show popular default models
-            let default_models = vec![
-                "gemini-1.5-pro-latest",
-                "gemini-1.5-flash-latest",
-                "gemini-1.5-flash-8b-latest",
-                "gemini-2.0-flash-exp",
-            ];
-            for model in default_models {
-                out.push(ListedModel {
-                    model: model.to_string(),
-                    adapter_kind: "Gemini".to_string(),
-                    enabled: true,
-                });
-            }
-        }
+/// Build an Ollama provider for the URL and credentials configured in settings.
+pub fn ollama_provider(app: &tauri::AppHandle) -> AppResult<OllamaProvider> {
+    let settings = read_settings(app)?;
+    OllamaProvider::from_settings(&settings)
+}
+
+/// Build every provider from the current settings.
+pub fn provider_registry(app: &tauri::AppHandle) -> AppResult<ProviderRegistry> {
+    let settings = read_settings(app)?;
+    Ok(ProviderRegistry::from_settings(&settings))
+}
+
+#[tauri::command]
+pub async fn get_adapter_models(
+    app: tauri::AppHandle,
+    adapter_kind: String,
+) -> AppResult<Vec<String>> {
+    let provider = provider_registry(&app)?.get(&adapter_kind)?;
+    provider.list_models().await
+}
+
+/// Check that a provider is reachable with the configured URL and credentials.
+#[tauri::command]
+pub async fn check_provider_health(
+    app: tauri::AppHandle,
+    adapter_kind: String,
+) -> AppResult<ProviderHealth> {
+    let provider = provider_registry(&app)?.get(&adapter_kind)?;
+    Ok(provider.health().await)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatInput {
+    pub conversation_id: String,
+    pub message_id: Option<String>,
+    pub adapter_kind: Option<String>, // Defaults to "Ollama"
+    pub model: String,
+    pub messages: Vec<serde_json::Value>,
+    pub options: Option<GenerationOptions>,
+    pub tools: Option<Vec<ToolDefinition>>,
+}
+
+/// Resolve the options for a generation: per-model defaults from settings, then the
+/// conversation's stored options, then the per-request overrides.
+pub async fn resolve_generation_options(
+    app: &tauri::AppHandle,
+    conversation_id: &str,
+    model: &str,
+    request: Option<&GenerationOptions>,
+) -> AppResult<GenerationOptions> {
+    let settings = read_settings(app)?;
+    let mut options = settings
+        .model_options
+        .as_ref()
+        .and_then(|defaults| defaults.get(model))
+        .cloned()
+        .unwrap_or_default();
+    if let Some(conversation) = read_conversation_options(app, conversation_id).await? {
+        options = options.merged_with(&conversation);
+    }
+    if let Some(request) = request {
+        options = options.merged_with(request);
     }
+    Ok(options)
+}
 
-    // Groq - enabled if API key is set
-    let groq_has_api_key = settings.groq_api_key.is_some();
-
-    if groq_has_api_key {
-        // Add manually configured models first
-        if let Some(models) = settings.groq_models.clone() {
-            for m in models {
-                out.push(ListedModel {
-                    model: m,
-                    adapter_kind: "Groq".to_string(),
-                    enabled: true,
-                });
-            }
-        }
+/// Start an Ollama generation and stream it to the webview.
+#[tauri::command]
+pub async fn stream_ollama_chat(
+    app: tauri::AppHandle,
+    input: ChatInput,
+) -> AppResult<ChatStreamStart> {
+    let input = ChatInput {
+        adapter_kind: Some("Ollama".to_string()),
+        ..input
+    };
+    stream_provider_chat(app, input).await
+}
 
-        // If no manual models configured, use default models
-        if settings.groq_models.is_none() {
-            // Show popular default models
-            let default_models = vec![
-                "llama-3.1-70b-versatile",
-                "llama-3.1-8b-instant",
-                "mixtral-8x7b-32768",
-                "gemma2-9b-it",
-            ];
-            for model in default_models {
-                out.push(ListedModel {
-                    model: model.to_string(),
-                    adapter_kind: "Groq".to_string(),
-                    enabled: true,
-                });
+/// Start a generation with the provider named by `adapter_kind` and stream it to the webview.
+///
+/// Returns as soon as the stream is open; tokens arrive as `chat_stream_token` events and
+/// requested tool calls as `chat_stream_tool_call` events, followed by either
+/// `chat_stream_end` or `chat_stream_error` for the same message id. To continue after
+/// tool calls, send the conversation again with the results as `tool` messages.
+#[tauri::command]
+pub async fn stream_provider_chat(
+    app: tauri::AppHandle,
+    input: ChatInput,
+) -> AppResult<ChatStreamStart> {
+    let adapter_kind = input.adapter_kind.as_deref().unwrap_or("Ollama");
+    let provider = provider_registry(&app)?.get(adapter_kind)?;
+
+    let options = resolve_generation_options(
+        &app,
+        &input.conversation_id,
+        &input.model,
+        input.options.as_ref(),
+    )
+    .await?;
+
+    let mut stream = provider
+        .stream_chat(ChatRequest {
+            model: input.model.clone(),
+            messages: input.messages,
+            options,
+            tools: input.tools.unwrap_or_default(),
+        })
+        .await?;
+
+    let conversation_id = input.conversation_id;
+    let message_id = input.message_id.unwrap_or_else(uuid);
+    let start = ChatStreamStart {
+        conversation_id: conversation_id.clone(),
+        message_id: message_id.clone(),
+    };
+    let _ = app.emit("chat_stream_start", start.clone());
+
+    let reply = Arc::new(Mutex::new(GeneratedReply::default()));
+
+    // Hold the registry lock until the task is registered so a short generation
+    // cannot finish and unregister itself before it was inserted.
+    let generations = app.state::<ActiveGenerations>();
+    let mut active = generations.0.lock()?;
+
+    let task = tauri::async_runtime::spawn({
+        let app = app.clone();
+        let reply = reply.clone();
+        let conversation_id = conversation_id.clone();
+        let message_id = message_id.clone();
+        async move {
+            let mut error = None;
+
+            while let Some(event) = stream.next().await {
+                match event {
+                    Ok(ChatEvent::Text(token)) => {
+                        if let Ok(mut reply) = reply.lock() {
+                            reply.push_text(&token);
+                        }
+
+                        // Emit token to frontend
+                        let _ = app.emit(
+                            "chat_stream_token",
+                            ChatStreamToken {
+                                conversation_id: conversation_id.clone(),
+                                message_id: message_id.clone(),
+                                token,
+                            },
+                        );
+                    }
+                    Ok(ChatEvent::Reasoning(token)) => {
+                        if let Ok(mut reply) = reply.lock() {
+                            reply.push_reasoning(&token);
+                        }
+                        let _ = app.emit(
+                            "chat_stream_reasoning",
+                            ChatStreamReasoning {
+                                conversation_id: conversation_id.clone(),
+                                message_id: message_id.clone(),
+                                token,
+                                signature: None,
+                            },
+                        );
+                    }
+                    Ok(ChatEvent::ReasoningSignature(signature)) => {
+                        if let Ok(mut reply) = reply.lock() {
+                            reply.sign_reasoning(signature.clone());
+                        }
+                        let _ = app.emit(
+                            "chat_stream_reasoning",
+                            ChatStreamReasoning {
+                                conversation_id: conversation_id.clone(),
+                                message_id: message_id.clone(),
+                                token: String::new(),
+                                signature: Some(signature),
+                            },
+                        );
+                    }
+                    Ok(ChatEvent::ToolCall(tool_call)) => {
+                        let _ = app.emit(
+                            "chat_stream_tool_call",
+                            ChatStreamToolCall {
+                                conversation_id: conversation_id.clone(),
+                                message_id: message_id.clone(),
+                                tool_call: tool_call.clone(),
+                            },
+                        );
+                        if let Ok(mut reply) = reply.lock() {
+                            reply.tool_calls.push(tool_call);
+                        }
+                    }
+                    Ok(ChatEvent::Usage(reported)) => {
+                        if let Ok(mut reply) = reply.lock() {
+                            reply.usage = Some(reported);
+                        }
+                    }
+                    Err(e) => {
+                        error = Some(e);
+                        break;
+                    }
+                }
             }
-        }
-    }
 
-    // OpenRouter - enabled if API key is set
-    let openrouter_has_api_key = settings.openrouter_api_key.is_some();
-
-    if openrouter_has_api_key {
-        // Add manually configured models first
-        if let Some(models) = settings.openrouter_models.clone() {
-            for m in models {
-                out.push(ListedModel {
-                    model: m,
-                    adapter_kind: "OpenRouter".to_string(),
-                    enabled: true,
-                });
+            if let Ok(mut active) = app.state::<ActiveGenerations>().0.lock() {
+                active.remove(&message_id);
             }
-        }
 
-        // If no manual models configured, try to fetch from API if API key is available
-        if settings.openrouter_models.is_none() && openrouter_has_api_key {
-            if let Ok(api_models) = get_adapter_models("OpenRouter".to_string()).await {
-                for m in api_models {
-                    out.push(ListedModel {
-                        model: m,
-                        adapter_kind: "OpenRouter".to_string(),
-                        enabled: true,
-                    });
+            match error {
+                Some(error) => {
+                    let _ = app.emit(
+                        "chat_stream_error",
+                        ChatStreamError {
+                            conversation_id,
+                            message_id,
+                            error,
+                        },
+                    );
                 }
-            } else {
-                // Fallback to default models if API fetch fails
-                let default_models = vec![
-                    "anthropic/claude-3.5-sonnet",
-                    "openai/gpt-4o",
-                    "google/gemini-pro-1.5",
-                    "meta-llama/llama-3.2-90b-instruct",
-                    "mistralai/mistral-large",
-                ];
-                for model in default_models {
-                    out.push(ListedModel {
-                        model: model.to_string(),
-                        adapter_kind: "OpenRouter".to_string(),
-                        enabled: true,
-                    });
+                None => {
+                    if let Ok(reply) = reply.lock() {
+                        let end = stream_end(conversation_id, message_id, &reply);
+                        let _ = app.emit("chat_stream_end", end);
+                    }
                 }
             }
-        } else if settings.openrouter_models.is_none() {
-            // Show popular default models
-            let default_models = vec![
-                "anthropic/claude-3.5-sonnet",
-                "openai/gpt-4o",
-                "google/gemini-pro-1.5",
-                "meta-llama/llama-3.2-90b-instruct",
-                "mistralai/mistral-large",
-            ];
-            for model in default_models {
-                out.push(ListedModel {
-                    model: model.to_string(),
-                    adapter_kind: "OpenRouter".to_string(),
-                    enabled: true,
-                });
-            }
         }
-    }
+    });
 
-    // DeepSeek - enabled if API key is set
-    let deepseek_has_api_key = settings.deepseek_api_key.is_some();
-
-    if deepseek_has_api_key {
-        // Add manually configured models first
-        if let Some(models) = settings.deepseek_models.clone() {
-            for m in models {
-                out.push(ListedModel {
-                    model: m,
-                    adapter_kind: "DeepSeek".to_string(),
-                    enabled: true,
-                });
-            }
-        }
+    active.insert(
+        message_id,
+        ActiveGeneration {
+            conversation_id,
+            reply,
+            task,
+        },
+    );
 
-        // If no manual models configured, use default models
-        if settings.deepseek_models.is_none() {
-            // Show popular default models
-            let default_models = vec![
-                "deepseek-chat",
-                "deepseek-coder",
-                "deepseek-reasoner",
-                "deepseek-chat-67b",
-                "deepseek-coder-33b",
-            ];
-            for model in default_models {
-                out.push(ListedModel {
-                    model: model.to_string(),
-                    adapter_kind: "DeepSeek".to_string(),
-                    enabled: true,
-                });
-            }
-        }
-    }
+    Ok(start)
+}
 
-    // Mistral - show popular models (no built-in settings support yet)
-    let default_mistral_models = vec![
-        "mistral-large-latest",
-        "mistral-medium-latest",
-        "mistral-small-latest",
-        "codestral-latest",
-    ];
-    for model in default_mistral_models {
-        out.push(ListedModel {
-            model: model.to_string(),
-            adapter_kind: "Mistral".to_string(),
-            enabled: false, // No API key support in settings yet
-        });
+fn stream_end(
+    conversation_id: String,
+    message_id: String,
+    reply: &GeneratedReply,
+) -> ChatStreamEnd {
+    ChatStreamEnd {
+        conversation_id,
+        message_id,
+        complete_content: reply.content.clone(),
+        tool_calls: reply.tool_calls.clone(),
+        usage: reply.usage,
     }
+}
 
-    // Ollama - try to fetch actual local models, fallback to defaults if Ollama is not running
-    let ollama_provider = OllamaProvider::new();
-    match ollama_provider.list_models().await {
-        Ok(local_models) => {
-            // Use actual local models
-            for model in local_models {
-                out.push(ListedModel {
-                    model,
-                    adapter_kind: "Ollama".to_string(),
-                    enabled: true, // Ollama runs locally, no API key needed
-                });
-            }
-        }
-        Err(e) => {
-            println!("Failed to fetch Ollama models ({}), using defaults", e);
-            // Fallback to default popular models if Ollama is not running
-            let default_ollama_models = vec![
-                "llama3.2:3b",
-                "llama3.1:8b",
-                "llama3.1:70b",
-                "qwen2.5:7b",
-                "codellama:7b",
-            ];
-            for model in default_ollama_models {
-                out.push(ListedModel {
-                    model: model.to_string(),
-                    adapter_kind: "Ollama".to_string(),
-                    enabled: false, // Mark as disabled since Ollama is not running
-                });
-            }
+/// Run a generation to the end and return its text, e.g. for conversation titles.
+///
+/// Nothing is streamed to the webview or saved; reasoning and tool calls are dropped.
+#[tauri::command]
+pub async fn complete_provider_chat(app: tauri::AppHandle, input: ChatInput) -> AppResult<String> {
+    let adapter_kind = input.adapter_kind.as_deref().unwrap_or("Ollama");
+    let provider = provider_registry(&app)?.get(adapter_kind)?;
+
+    let options = resolve_generation_options(
+        &app,
+        &input.conversation_id,
+        &input.model,
+        input.options.as_ref(),
+    )
+    .await?;
+
+    let mut stream = provider
+        .stream_chat(ChatRequest {
+            model: input.model,
+            messages: input.messages,
+            options,
+            tools: input.tools.unwrap_or_default(),
+        })
+        .await?;
+
+    let mut text = String::new();
+    while let Some(event) = stream.next().await {
+        if let ChatEvent::Text(token) = event? {
+            text.push_str(&token);
         }
     }
+    Ok(text)
+}
 
-    // Grok (X.AI) - show popular models (no built-in settings support yet)
-    let default_xai_models = vec!["grok-beta", "grok-vision-beta"];
-    for model in default_xai_models {
-        out.push(ListedModel {
-            model: model.to_string(),
-            adapter_kind: "X.AI".to_string(),
-            enabled: false, // No API key support in settings yet
-        });
-    }
+/// Abort in-flight generations for a conversation (or a single message when `message_id`
+/// is given), emit `chat_stream_cancelled` with a `cancelled` error and persist whatever
+/// was generated so far.
+#[tauri::command]
+pub async fn cancel_generation(
+    app: tauri::AppHandle,
+    conversation_id: String,
+    message_id: Option<String>,
+) -> AppResult<Vec<ChatStreamEnd>> {
+    let cancelled: Vec<(String, ActiveGeneration)> = {
+        let generations = app.state::<ActiveGenerations>();
+        let mut active = generations.0.lock()?;
+        let ids: Vec<String> = active
+            .iter()
+            .filter(|(id, generation)| {
+                generation.conversation_id == conversation_id
+                    && (message_id.is_none() || message_id.as_ref() == Some(*id))
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.into_iter()
+            .filter_map(|id| active.remove(&id).map(|generation| (id, generation)))
+            .collect()
+    };
 
-    // Debug: log the models being returned
-    println!("Returning {} models:", out.len());
-    for model in &out {
-        println!(
-            "  {} ({}) - enabled: {}",
-            model.model, model.adapter_kind, model.enabled
+    let mut out = Vec::new();
+    for (message_id, generation) in cancelled {
+        // Dropping the task drops the token stream, which stops the Ollama request
+        generation.task.abort();
+        let (end, message) = {
+            let reply = generation.reply.lock()?;
+            let end = stream_end(generation.conversation_id, message_id, &reply);
+            let message = (!reply.is_empty()).then(|| reply.to_ui_message(&end.message_id));
+            (end, message)
+        };
+        let _ = app.emit(
+            "chat_stream_cancelled",
+            ChatStreamCancelled {
+                end: end.clone(),
+                error: AppError::new(ErrorKind::Cancelled, "Generation cancelled"),
+            },
         );
+
+        if let Some(message) = message {
+            db_save_complete_message(
+                app.clone(),
+                SaveCompleteMessageInput {
+                    conversation_id: end.conversation_id.clone(),
+                    message,
+                },
+            )
+            .await?;
+        }
+
+        out.push(end);
     }
 
     Ok(out)
 }
 
 #[tauri::command]
-pub async fn get_adapter_models(adapter_kind: String) -> Result<Vec<String>, String> {
-    match adapter_kind.as_str() {
-        "Ollama" => {
-            // Use our custom OllamaProvider for better integration
-            let provider = OllamaProvider::new();
-            provider.list_models().await
-        }
-        _ => {
-            // All other providers are handled by the frontend AI SDK
-            Err(format!(
-                "Provider {} is handled by the frontend",
-                adapter_kind
-            ))
-        }
-    }
+pub async fn get_ollama_model_info(
+    app: tauri::AppHandle,
+    model_name: String,
+) -> AppResult<ModelDetails> {
+    let provider = ollama_provider(&app)?;
+    provider.model_details(&model_name).await
 }
 
-#[derive(Debug, Clone, Deserialize)]
-pub struct OllamaChatInput {
-    pub model: String,
-    pub messages: Vec<serde_json::Value>,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn keeps_every_part_of_a_reply() {
+        let mut reply = GeneratedReply::default();
+        reply.push_reasoning("Think");
+        reply.push_reasoning("ing");
+        reply.sign_reasoning("sig".to_string());
+        reply.push_reasoning("More");
+        reply.push_text("Checking.");
+        reply.tool_calls.push(ToolCall {
+            id: "call_1".to_string(),
+            name: "get_weather".to_string(),
+            arguments: json!({ "city": "Oslo" }),
+        });
+        reply.usage = Some(Usage {
+            input_tokens: 10,
+            output_tokens: 5,
+        });
 
-#[tauri::command]
-pub async fn stream_ollama_chat(
-    _app: tauri::AppHandle,
-    input: OllamaChatInput,
-) -> Result<Vec<String>, String> {
-    // Extract the user message from the messages array
-    let user_message = input
-        .messages
-        .iter()
-        .find(|msg| msg["role"] == "user")
-        .and_then(|msg| msg["content"].as_str())
-        .unwrap_or("Hello");
-
-    // Use the Ollama provider directly
-    let provider = OllamaProvider::new();
-    match provider.stream_chat(&input.model, user_message).await {
-        Ok(mut stream) => {
-            use futures::StreamExt;
-            let mut tokens = Vec::new();
-
-            while let Some(token_result) = stream.next().await {
-                match token_result {
-                    Ok(token) => {
-                        tokens.push(token);
-                    }
-                    Err(e) => {
-                        return Err(format!("Stream error: {}", e));
+        assert_eq!(
+            reply.to_ui_message("m1"),
+            json!({
+                "id": "m1",
+                "role": "assistant",
+                "parts": [
+                    { "type": "step-start" },
+                    {
+                        "type": "reasoning",
+                        "text": "Thinking",
+                        "state": "done",
+                        "providerMetadata": { "anthropic": { "signature": "sig" } }
+                    },
+                    { "type": "reasoning", "text": "More", "state": "done" },
+                    { "type": "text", "text": "Checking.", "state": "done" },
+                    {
+                        "type": "tool-get_weather",
+                        "toolCallId": "call_1",
+                        "state": "input-available",
+                        "input": { "city": "Oslo" }
                     }
+                ],
+                "metadata": {
+                    "usage": { "inputTokens": 10, "outputTokens": 5, "totalTokens": 15 }
                 }
-            }
-
-            Ok(tokens)
-        }
-        Err(e) => Err(format!("Failed to start Ollama stream: {}", e)),
+            })
+        );
     }
-}
 
-#[tauri::command]
-pub async fn get_ollama_model_info(model_name: String) -> Result<String, String> {
-    let provider = OllamaProvider::new();
-    provider.get_model_info(&model_name).await
+    #[test]
+    fn has_nothing_to_save_before_the_first_token() {
+        let mut reply = GeneratedReply::default();
+        assert!(reply.is_empty());
+
+        reply.usage = Some(Usage::default());
+        assert!(reply.is_empty());
+    }
 }
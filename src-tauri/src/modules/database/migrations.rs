@@ -0,0 +1,457 @@
+use super::{search, stats};
+use crate::modules::error::{AppError, AppResult, Context, ErrorKind};
+use rusqlite::{Connection, Transaction};
+use std::path::Path;
+
+type Migration = fn(&Transaction) -> rusqlite::Result<()>;
+
+/// Schema changes in order. A database at `user_version` N has the first N applied;
+/// append new migrations and never edit released ones.
+const MIGRATIONS: &[Migration] = &[baseline, search_index, conversation_stats];
+
+/// Schema version this build of the app writes.
+pub const SCHEMA_VERSION: i64 = MIGRATIONS.len() as i64;
+
+/// Bring the database at `path` up to `SCHEMA_VERSION`.
+///
+/// Pending migrations run in one transaction, after the file has been copied next to
+/// itself as `app.db.v{N}.bak`. A database from a newer app version is refused rather
+/// than opened with a schema this build does not know.
+pub fn migrate(conn: &mut Connection, path: &Path) -> AppResult<()> {
+    let version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .context("read schema version")?;
+    if version > SCHEMA_VERSION {
+        return Err(AppError::new(
+            ErrorKind::Database,
+            format!(
+                "{} was created by a newer version of the app (schema {}, this version supports {}). Update the app to open it.",
+                path.display(),
+                version,
+                SCHEMA_VERSION
+            ),
+        ));
+    }
+    if version == SCHEMA_VERSION {
+        return Ok(());
+    }
+
+    if has_tables(conn)? {
+        backup(conn, path, version)?;
+    }
+
+    // Databases from before foreign keys were enforced can hold rows whose parent is
+    // gone, which would fail migrations that copy them into tables with constraints.
+    // The pragma cannot change inside a transaction, so it is off for the whole run.
+    let foreign_keys: bool = conn
+        .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+        .context("migrate")?;
+    conn.pragma_update(None, "foreign_keys", false)
+        .context("migrate")?;
+    let result = run_migrations(conn, version);
+    conn.pragma_update(None, "foreign_keys", foreign_keys)
+        .context("migrate")?;
+    result
+}
+
+fn run_migrations(conn: &mut Connection, version: i64) -> AppResult<()> {
+    let tx = conn.transaction().context("migrate")?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(&tx).context(&format!("migration {}", index + 1))?;
+    }
+    remove_orphans(&tx).context("migrate")?;
+    // user_version is part of the database header, so it commits with the migrations
+    tx.pragma_update(None, "user_version", SCHEMA_VERSION)
+        .context("migrate")?;
+    tx.commit().context("migrate")?;
+    Ok(())
+}
+
+/// Delete rows whose parent no longer exists, as the cascades would have. Cascades do
+/// not run with foreign keys off, so removing a row can orphan its own children; repeat
+/// until `foreign_key_check` comes back clean.
+fn remove_orphans(tx: &Transaction) -> rusqlite::Result<()> {
+    loop {
+        let orphans: Vec<(String, i64)> = tx
+            .prepare("PRAGMA foreign_key_check")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        if orphans.is_empty() {
+            return Ok(());
+        }
+        for (table, rowid) in orphans {
+            log::warn!("Removing {} row {} whose parent is missing", table, rowid);
+            tx.execute(
+                &format!("DELETE FROM \"{}\" WHERE rowid = ?", table),
+                [rowid],
+            )?;
+        }
+    }
+}
+
+fn has_tables(conn: &Connection) -> AppResult<bool> {
+    let count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'",
+            [],
+            |row| row.get(0),
+        )
+        .context("read schema")?;
+    Ok(count > 0)
+}
+
+/// Copy the database before upgrading it. `VACUUM INTO` writes a consistent copy that
+/// includes changes still in the WAL.
+fn backup(conn: &Connection, path: &Path, version: i64) -> AppResult<()> {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".v{}.bak", version));
+    let backup = Path::new(&backup);
+    if backup.exists() {
+        std::fs::remove_file(backup).context("remove old database backup")?;
+    }
+    conn.execute("VACUUM INTO ?", [backup.to_string_lossy().into_owned()])
+        .context("back up database")?;
+    Ok(())
+}
+
+/// 1: conversations and messages. Databases from before versioning may already have
+/// the tables, with or without the columns that used to be added on every open.
+fn baseline(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS conversations (
+          id TEXT PRIMARY KEY,
+          title TEXT NOT NULL,
+          created_at TEXT NOT NULL,
+          archived INTEGER NOT NULL DEFAULT 0,
+          model TEXT,
+          options TEXT
+        );
+        CREATE TABLE IF NOT EXISTS messages (
+          id TEXT PRIMARY KEY,
+          conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+          role TEXT NOT NULL,
+          content TEXT NOT NULL,
+          created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_messages_conversation_id_created_at
+          ON messages(conversation_id, created_at);
+        "#,
+    )?;
+    add_missing_column(
+        tx,
+        "conversations",
+        "archived",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    add_missing_column(tx, "conversations", "model", "TEXT")?;
+    add_missing_column(tx, "conversations", "options", "TEXT")?; // Generation options (JSON)
+    Ok(())
+}
+
+/// 2: full-text index over message text and conversation titles. `search_entries` says
+/// what each `search_index` row belongs to; title entries have no `message_id`. Entries
+/// follow their message or conversation out through the cascades, and the trigger drops
+/// the indexed text with them. Inserts and edits are indexed by the commands that write
+/// them, since the text has to be extracted from the stored JSON.
+fn search_index(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE search_entries (
+          id INTEGER PRIMARY KEY,
+          conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+          message_id TEXT UNIQUE REFERENCES messages(id) ON DELETE CASCADE
+        );
+        CREATE INDEX idx_search_entries_conversation_id ON search_entries(conversation_id);
+        CREATE VIRTUAL TABLE search_index USING fts5(
+          text,
+          tokenize = 'unicode61 remove_diacritics 2'
+        );
+        CREATE TRIGGER search_entries_deleted AFTER DELETE ON search_entries BEGIN
+          DELETE FROM search_index WHERE rowid = old.id;
+        END;
+        "#,
+    )?;
+
+    let mut stmt = tx.prepare("SELECT id, title FROM conversations")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        search::index_title(tx, &row.get::<_, String>(0)?, &row.get::<_, String>(1)?)?;
+    }
+    let mut stmt = tx.prepare("SELECT id, conversation_id, content FROM messages")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        search::index_message(
+            tx,
+            &row.get::<_, String>(0)?,
+            &row.get::<_, String>(1)?,
+            &row.get::<_, String>(2)?,
+        )?;
+    }
+    Ok(())
+}
+
+/// 3: per-message preview and token count, and per-conversation totals kept up to date
+/// by triggers. `updated_at` is the newest message's time, or the creation time of an
+/// empty conversation. The totals are recomputed rather than adjusted so upserts, which
+/// replace a row without firing the delete trigger, cannot make them drift.
+fn conversation_stats(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        r#"
+        ALTER TABLE messages ADD COLUMN preview TEXT;
+        ALTER TABLE messages ADD COLUMN tokens INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE conversations ADD COLUMN updated_at TEXT;
+        ALTER TABLE conversations ADD COLUMN message_count INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE conversations ADD COLUMN last_message_preview TEXT;
+        ALTER TABLE conversations ADD COLUMN total_tokens INTEGER NOT NULL DEFAULT 0;
+        "#,
+    )?;
+
+    let mut stmt = tx.prepare("SELECT id, content FROM messages")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let id: String = row.get(0)?;
+        let content: String = row.get(1)?;
+        tx.execute(
+            "UPDATE messages SET preview = ?, tokens = ? WHERE id = ?",
+            rusqlite::params![
+                stats::message_preview(&content),
+                stats::message_tokens(&content),
+                id
+            ],
+        )?;
+    }
+
+    let refresh = |conversation_id: &str| {
+        format!(
+            r#"
+            UPDATE conversations SET
+              message_count = (SELECT COUNT(*) FROM messages WHERE conversation_id = {id}),
+              total_tokens = (SELECT COALESCE(SUM(tokens), 0) FROM messages WHERE conversation_id = {id}),
+              last_message_preview = (
+                SELECT preview FROM messages WHERE conversation_id = {id}
+                ORDER BY julianday(created_at) DESC, id DESC LIMIT 1
+              ),
+              updated_at = COALESCE(
+                (SELECT created_at FROM messages WHERE conversation_id = {id}
+                 ORDER BY julianday(created_at) DESC, id DESC LIMIT 1),
+                created_at
+              )
+            WHERE id = {id};
+            "#,
+            id = conversation_id
+        )
+    };
+    tx.execute_batch(&format!(
+        r#"
+        {all}
+        CREATE INDEX idx_conversations_updated_at
+          ON conversations(julianday(updated_at), id);
+        CREATE TRIGGER conversation_stats_message_inserted AFTER INSERT ON messages BEGIN
+          {new}
+        END;
+        CREATE TRIGGER conversation_stats_message_updated AFTER UPDATE ON messages BEGIN
+          {old}
+          {new}
+        END;
+        CREATE TRIGGER conversation_stats_message_deleted AFTER DELETE ON messages BEGIN
+          {old}
+        END;
+        "#,
+        all = refresh("conversations.id"),
+        new = refresh("new.conversation_id"),
+        old = refresh("old.conversation_id"),
+    ))?;
+    Ok(())
+}
+
+fn add_missing_column(
+    tx: &Transaction,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> rusqlite::Result<()> {
+    let exists: bool = tx.query_row(
+        &format!(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('{}') WHERE name = ?",
+            table
+        ),
+        [column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        tx.execute_batch(&format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            table, column, definition
+        ))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A database path in its own temporary directory, removed on drop.
+    struct TempDb(PathBuf);
+
+    impl TempDb {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("app-db-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir(&dir).unwrap();
+            Self(dir.join("app.db"))
+        }
+    }
+
+    impl Drop for TempDb {
+        fn drop(&mut self) {
+            if let Some(dir) = self.0.parent() {
+                let _ = std::fs::remove_dir_all(dir);
+            }
+        }
+    }
+
+    fn user_version(conn: &Connection) -> i64 {
+        conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn migrates_a_new_database_once() {
+        let db = TempDb::new();
+        let mut conn = Connection::open(&db.0).unwrap();
+        migrate(&mut conn, &db.0).unwrap();
+        assert_eq!(user_version(&conn), SCHEMA_VERSION);
+        // Nothing to back up in an empty database
+        assert!(!db.0.with_file_name("app.db.v0.bak").exists());
+
+        migrate(&mut conn, &db.0).unwrap();
+        assert_eq!(user_version(&conn), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn upgrades_a_database_from_before_versioning() {
+        let db = TempDb::new();
+        let mut conn = Connection::open(&db.0).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE conversations (id TEXT PRIMARY KEY, title TEXT NOT NULL, created_at TEXT NOT NULL);
+            CREATE TABLE messages (id TEXT PRIMARY KEY, conversation_id TEXT NOT NULL, role TEXT NOT NULL, content TEXT NOT NULL, created_at TEXT NOT NULL);
+            INSERT INTO conversations VALUES ('c1', 'Trip planning', '2024-01-01T00:00:00Z');
+            INSERT INTO messages VALUES ('m1', 'c1', 'user', '{"parts":[{"type":"text","text":"Pack for   Oslo"}]}', '2024-01-01T00:01:00Z');
+            INSERT INTO messages VALUES ('m2', 'c1', 'assistant', '{"parts":[{"type":"text","text":"Bring a coat."}],"metadata":{"usage":{"totalTokens":42}}}', '2024-01-01T00:02:00Z');
+            "#,
+        )
+        .unwrap();
+
+        migrate(&mut conn, &db.0).unwrap();
+        assert_eq!(user_version(&conn), SCHEMA_VERSION);
+        assert!(db.0.with_file_name("app.db.v0.bak").exists());
+
+        let (archived, count, preview, tokens, updated_at): (i64, i64, String, i64, String) = conn
+            .query_row(
+                "SELECT archived, message_count, last_message_preview, total_tokens, updated_at FROM conversations WHERE id = 'c1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+            )
+            .unwrap();
+        assert_eq!(archived, 0);
+        assert_eq!(count, 2);
+        assert_eq!(preview, "Bring a coat.");
+        assert_eq!(tokens, 42);
+        assert_eq!(updated_at, "2024-01-01T00:02:00Z");
+
+        let matches: Vec<Option<String>> = conn
+            .prepare(
+                "SELECT e.message_id FROM search_index JOIN search_entries e ON e.id = search_index.rowid WHERE search_index MATCH ? ORDER BY e.id",
+            )
+            .unwrap()
+            .query_map(["oslo OR trip"], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(matches, [None, Some("m1".to_string())]);
+    }
+
+    #[test]
+    fn upgrades_a_database_with_orphaned_messages() {
+        let db = TempDb::new();
+        let mut conn = Connection::open(&db.0).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE conversations (id TEXT PRIMARY KEY, title TEXT NOT NULL, created_at TEXT NOT NULL);
+            CREATE TABLE messages (id TEXT PRIMARY KEY, conversation_id TEXT NOT NULL, role TEXT NOT NULL, content TEXT NOT NULL, created_at TEXT NOT NULL);
+            INSERT INTO conversations VALUES ('c1', 'Kept', '2024-01-01T00:00:00Z');
+            INSERT INTO messages VALUES ('m1', 'c1', 'user', 'Hi', '2024-01-01T00:01:00Z');
+            INSERT INTO messages VALUES ('m2', 'deleted', 'user', 'Lost', '2024-01-01T00:02:00Z');
+            PRAGMA foreign_keys = ON;
+            "#,
+        )
+        .unwrap();
+
+        migrate(&mut conn, &db.0).unwrap();
+        assert_eq!(user_version(&conn), SCHEMA_VERSION);
+
+        let count = |sql: &str| -> i64 { conn.query_row(sql, [], |row| row.get(0)).unwrap() };
+        // The old messages table has no constraint to enforce, but the orphan is not indexed
+        assert_eq!(count("SELECT COUNT(*) FROM messages"), 2);
+        assert_eq!(count("SELECT COUNT(*) FROM search_entries"), 2);
+        assert_eq!(count("SELECT COUNT(*) FROM search_index"), 2);
+        assert_eq!(count("SELECT COUNT(*) FROM pragma_foreign_key_check"), 0);
+        assert_eq!(count("PRAGMA foreign_keys"), 1);
+    }
+
+    #[test]
+    fn triggers_keep_conversation_stats_current() {
+        let db = TempDb::new();
+        let mut conn = Connection::open(&db.0).unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = ON").unwrap();
+        migrate(&mut conn, &db.0).unwrap();
+        conn.execute_batch(
+            r#"
+            INSERT INTO conversations (id, title, created_at) VALUES ('c1', 'Empty', '2024-01-01T00:00:00Z');
+            INSERT INTO messages (id, conversation_id, role, content, created_at, preview, tokens)
+              VALUES ('m1', 'c1', 'user', 'Hi', '2024-01-02T00:00:00Z', 'Hi', 5),
+                     ('m2', 'c1', 'assistant', 'Hello', '2024-01-03T00:00:00Z', 'Hello', 7);
+            INSERT OR REPLACE INTO messages (id, conversation_id, role, content, created_at, preview, tokens)
+              VALUES ('m2', 'c1', 'assistant', 'Hello!', '2024-01-03T00:00:00Z', 'Hello!', 9);
+            "#,
+        )
+        .unwrap();
+        let stats = |conn: &Connection| -> (i64, i64, Option<String>, String) {
+            conn.query_row(
+                "SELECT message_count, total_tokens, last_message_preview, updated_at FROM conversations WHERE id = 'c1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            stats(&conn),
+            (
+                2,
+                14,
+                Some("Hello!".to_string()),
+                "2024-01-03T00:00:00Z".to_string()
+            )
+        );
+
+        conn.execute("DELETE FROM messages", []).unwrap();
+        assert_eq!(
+            stats(&conn),
+            (0, 0, None, "2024-01-01T00:00:00Z".to_string())
+        );
+    }
+
+    #[test]
+    fn refuses_a_newer_schema() {
+        let db = TempDb::new();
+        let mut conn = Connection::open(&db.0).unwrap();
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+        let error = migrate(&mut conn, &db.0).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Database);
+        assert_eq!(user_version(&conn), SCHEMA_VERSION + 1);
+    }
+}
@@ -1,6 +1,19 @@
+pub mod migrations;
+pub mod page;
+pub mod pool;
+pub mod search;
+pub mod stats;
+
+pub use page::{Page, PageInput};
+pub use pool::Database;
+pub use search::{db_search, SearchHit, SearchInput};
+
+use crate::modules::error::{AppError, AppResult, Context};
+use crate::modules::providers::GenerationOptions;
 use crate::modules::utils::uuid;
 use chrono::Utc;
-use rusqlite::{params, Connection};
+use page::{Cursor, PageRequest};
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use std::{fs, path::PathBuf};
 use tauri::Manager;
@@ -12,6 +25,29 @@ pub struct Conversation {
     pub created_at: String,
     pub archived: i64,
     pub model: Option<String>,
+    pub updated_at: String, // Time of the newest message, or created_at when there is none
+    pub message_count: i64,
+    pub last_message_preview: Option<String>,
+    pub total_tokens: i64,
+}
+
+const CONVERSATION_COLUMNS: &str = "id, title, created_at, archived, model, updated_at, message_count, last_message_preview, total_tokens";
+
+impl Conversation {
+    /// Read a row selected with `CONVERSATION_COLUMNS`.
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            created_at: row.get(2)?,
+            archived: row.get(3)?,
+            model: row.get(4)?,
+            updated_at: row.get(5)?,
+            message_count: row.get(6)?,
+            last_message_preview: row.get(7)?,
+            total_tokens: row.get(8)?,
+        })
+    }
 }
 
 // Legacy Message struct for backward compatibility
@@ -50,6 +86,16 @@ pub struct CreateConversationInput {
     pub model: Option<String>,
 }
 
+/// Filters and page for `db_list_conversations`; conversations come most recently
+/// active first.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ConversationQuery {
+    pub archived: Option<bool>,
+    pub model: Option<String>,
+    #[serde(flatten)]
+    pub page: PageInput,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct AddMessageInput {
     pub conversation_id: String,
@@ -72,106 +118,135 @@ pub struct SaveCompleteMessageInput {
     pub message: serde_json::Value, // Full AI SDK message object
 }
 
-pub fn db_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
-    let mut dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("paths: {e}"))?;
+pub fn db_path(app: &tauri::AppHandle) -> AppResult<PathBuf> {
+    let mut dir = app.path().app_data_dir().context("paths")?;
     if !dir.exists() {
-        fs::create_dir_all(&dir).map_err(|e| format!("create app data dir: {e}"))?;
+        fs::create_dir_all(&dir).context("create app data dir")?;
     }
     dir.push("app.db");
     Ok(dir)
 }
 
-pub fn get_conn(app: &tauri::AppHandle) -> Result<Connection, String> {
-    let path = db_path(app)?;
-    let conn = Connection::open(path).map_err(|e| format!("open db: {e}"))?;
-    conn.execute_batch(
-        r#"
-        PRAGMA journal_mode = WAL;
-        CREATE TABLE IF NOT EXISTS conversations (
-          id TEXT PRIMARY KEY,
-          title TEXT NOT NULL,
-          created_at TEXT NOT NULL,
-          archived INTEGER NOT NULL DEFAULT 0,
-          model TEXT
-        );
-        CREATE TABLE IF NOT EXISTS messages (
-          id TEXT PRIMARY KEY,
-          conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
-          role TEXT NOT NULL,
-          content TEXT NOT NULL,
-          created_at TEXT NOT NULL
-        );
-        CREATE INDEX IF NOT EXISTS idx_messages_conversation_id_created_at
-          ON messages(conversation_id, created_at);
-        "#,
+/// Generation options stored for a conversation, if any.
+pub async fn read_conversation_options(
+    app: &tauri::AppHandle,
+    conversation_id: &str,
+) -> AppResult<Option<GenerationOptions>> {
+    let conversation_id = conversation_id.to_string();
+    let options: Option<String> = app
+        .state::<Database>()
+        .run(move |conn| {
+            Ok(conn
+                .query_row(
+                    "SELECT options FROM conversations WHERE id = ?",
+                    params![conversation_id],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .flatten())
+        })
+        .await?;
+    match options {
+        Some(json) => serde_json::from_str(&json)
+            .map(Some)
+            .context("Failed to parse conversation options"),
+        None => Ok(None),
+    }
+}
+
+fn query_conversation(conn: &Connection, id: &str) -> AppResult<Conversation> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM conversations WHERE id = ?",
+            CONVERSATION_COLUMNS
+        ),
+        params![id],
+        Conversation::from_row,
     )
-    .map_err(|e| format!("migrate: {e}"))?;
-    // Try to add archived column if upgrading
-    let _ = conn.execute(
-        "ALTER TABLE conversations ADD COLUMN archived INTEGER NOT NULL DEFAULT 0",
-        [],
-    );
-    // Try to add model column if upgrading
-    let _ = conn.execute("ALTER TABLE conversations ADD COLUMN model TEXT", []);
-    Ok(conn)
+    .optional()?
+    .ok_or_else(|| AppError::not_found("conversation not found"))
 }
 
 #[tauri::command]
-pub async fn db_list_conversations(app: tauri::AppHandle) -> Result<Vec<Conversation>, String> {
-    let conn = get_conn(&app)?;
-    let mut stmt = conn.prepare("SELECT id, title, created_at, archived, model FROM conversations ORDER BY datetime(created_at) DESC").map_err(|e| e.to_string())?;
-    let rows = stmt
-        .query_map([], |row| {
-            Ok(Conversation {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                created_at: row.get(2)?,
-                archived: row.get(3)?,
-                model: row.get(4)?,
-            })
+pub async fn db_list_conversations(
+    app: tauri::AppHandle,
+    query: Option<ConversationQuery>,
+) -> AppResult<Page<Conversation>> {
+    let query = query.unwrap_or_default();
+    let request = PageRequest::new(query.page)?;
+    app.state::<Database>()
+        .run(move |conn| {
+            let mut stmt = conn.prepare(&format!(
+                r#"
+                SELECT {columns} FROM conversations
+                WHERE (?1 IS NULL OR archived = ?1)
+                  AND (?2 IS NULL OR model = ?2)
+                  AND (?3 IS NULL OR (julianday(updated_at), id) < (julianday(?3), ?4))
+                  AND (?5 IS NULL OR (julianday(updated_at), id) > (julianday(?5), ?6))
+                ORDER BY julianday(updated_at) {order}, id {order}
+                LIMIT ?7
+                "#,
+                columns = CONVERSATION_COLUMNS,
+                order = request.order()
+            ))?;
+            let rows = stmt.query_map(
+                params![
+                    query.archived.map(i64::from),
+                    query.model,
+                    request.before.as_ref().map(|c| &c.at),
+                    request.before.as_ref().map(|c| &c.id),
+                    request.after.as_ref().map(|c| &c.at),
+                    request.after.as_ref().map(|c| &c.id),
+                    request.fetch_limit()
+                ],
+                |row| {
+                    let conversation = Conversation::from_row(row)?;
+                    Ok((
+                        Cursor::encode(&conversation.updated_at, &conversation.id),
+                        conversation,
+                    ))
+                },
+            )?;
+            let mut out = Vec::new();
+            for r in rows {
+                out.push(r?);
+            }
+            Ok(request.page(out, true))
         })
-        .map_err(|e| e.to_string())?;
-    let mut out = Vec::new();
-    for r in rows {
-        out.push(r.map_err(|e| e.to_string())?);
-    }
-    Ok(out)
+        .await
 }
 
 #[tauri::command]
 pub async fn db_get_messages(
     app: tauri::AppHandle,
     conversation_id: String,
-) -> Result<Vec<Message>, String> {
-    let conn = get_conn(&app)?;
-    let mut stmt = conn.prepare("SELECT id, conversation_id, role, content, created_at FROM messages WHERE conversation_id = ? ORDER BY datetime(created_at) ASC").map_err(|e| e.to_string())?;
-    let rows = stmt
-        .query_map([conversation_id], |row| {
-            Ok(Message {
-                id: row.get(0)?,
-                conversation_id: row.get(1)?,
-                role: row.get(2)?,
-                content: row.get(3)?,
-                created_at: row.get(4)?,
-            })
+) -> AppResult<Vec<Message>> {
+    app.state::<Database>()
+        .run(move |conn| {
+            let mut stmt = conn.prepare("SELECT id, conversation_id, role, content, created_at FROM messages WHERE conversation_id = ? ORDER BY datetime(created_at) ASC")?;
+            let rows = stmt.query_map([conversation_id], |row| {
+                Ok(Message {
+                    id: row.get(0)?,
+                    conversation_id: row.get(1)?,
+                    role: row.get(2)?,
+                    content: row.get(3)?,
+                    created_at: row.get(4)?,
+                })
+            })?;
+            let mut out = Vec::new();
+            for r in rows {
+                out.push(r?);
+            }
+            Ok(out)
         })
-        .map_err(|e| e.to_string())?;
-    let mut out = Vec::new();
-    for r in rows {
-        out.push(r.map_err(|e| e.to_string())?);
-    }
-    Ok(out)
+        .await
 }
 
 #[tauri::command]
 pub async fn db_create_conversation(
     app: tauri::AppHandle,
     input: Option<CreateConversationInput>,
-) -> Result<Conversation, String> {
-    let conn = get_conn(&app)?;
+) -> AppResult<Conversation> {
     let id = uuid().to_string();
     let created_at = Utc::now().to_rfc3339();
     let title = input
@@ -179,52 +254,71 @@ pub async fn db_create_conversation(
         .and_then(|i| i.title.clone())
         .unwrap_or_else(|| "New Chat".to_string());
     let model = input.as_ref().and_then(|i| i.model.clone());
-    conn.execute(
-        "INSERT INTO conversations (id, title, created_at, archived, model) VALUES (?, ?, ?, 0, ?)",
-        params![id, title, created_at, model],
-    )
-    .map_err(|e| e.to_string())?;
-    Ok(Conversation {
-        id,
-        title,
-        created_at,
-        archived: 0,
-        model,
-    })
+    app.state::<Database>()
+        .run(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "INSERT INTO conversations (id, title, created_at, archived, model, updated_at) VALUES (?, ?, ?, 0, ?, ?)",
+                params![id, title, created_at, model, created_at],
+            )?;
+            search::index_title(&tx, &id, &title)?;
+            tx.commit()?;
+            Ok(Conversation {
+                id,
+                title,
+                updated_at: created_at.clone(),
+                created_at,
+                archived: 0,
+                model,
+                message_count: 0,
+                last_message_preview: None,
+                total_tokens: 0,
+            })
+        })
+        .await
 }
 
 #[tauri::command]
-pub async fn db_add_message(
-    app: tauri::AppHandle,
-    input: AddMessageInput,
-) -> Result<Message, String> {
-    let conn = get_conn(&app)?;
+pub async fn db_add_message(app: tauri::AppHandle, input: AddMessageInput) -> AppResult<Message> {
     let id = uuid().to_string();
     let created_at = Utc::now().to_rfc3339();
-    conn.execute(
-        "INSERT INTO messages (id, conversation_id, role, content, created_at) VALUES (?, ?, ?, ?, ?)",
-        params![id, input.conversation_id, input.role, input.content, created_at],
-    ).map_err(|e| e.to_string())?;
-    Ok(Message {
-        id,
-        conversation_id: input.conversation_id,
-        role: input.role,
-        content: input.content,
-        created_at,
-    })
+    app.state::<Database>()
+        .run(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "INSERT INTO messages (id, conversation_id, role, content, created_at, preview, tokens) VALUES (?, ?, ?, ?, ?, ?, ?)",
+                params![
+                    id,
+                    input.conversation_id,
+                    input.role,
+                    input.content,
+                    created_at,
+                    stats::message_preview(&input.content),
+                    stats::message_tokens(&input.content)
+                ],
+            )?;
+            search::index_message(&tx, &id, &input.conversation_id, &input.content)?;
+            tx.commit()?;
+            Ok(Message {
+                id,
+                conversation_id: input.conversation_id,
+                role: input.role,
+                content: input.content,
+                created_at,
+            })
+        })
+        .await
 }
 
 #[tauri::command]
-pub async fn db_delete_conversation(app: tauri::AppHandle, id: String) -> Result<(), String> {
-    let conn = get_conn(&app)?;
-    conn.execute(
-        "DELETE FROM messages WHERE conversation_id = ?",
-        params![id],
-    )
-    .map_err(|e| e.to_string())?;
-    conn.execute("DELETE FROM conversations WHERE id = ?", params![id])
-        .map_err(|e| e.to_string())?;
-    Ok(())
+pub async fn db_delete_conversation(app: tauri::AppHandle, id: String) -> AppResult<()> {
+    app.state::<Database>()
+        .run(move |conn| {
+            // Messages go with it through ON DELETE CASCADE
+            conn.execute("DELETE FROM conversations WHERE id = ?", params![id])?;
+            Ok(())
+        })
+        .await
 }
 
 #[tauri::command]
@@ -232,14 +326,16 @@ pub async fn db_archive_conversation(
     app: tauri::AppHandle,
     id: String,
     archived: bool,
-) -> Result<(), String> {
-    let conn = get_conn(&app)?;
-    conn.execute(
-        "UPDATE conversations SET archived = ? WHERE id = ?",
-        params![if archived { 1 } else { 0 }, id],
-    )
-    .map_err(|e| e.to_string())?;
-    Ok(())
+) -> AppResult<()> {
+    app.state::<Database>()
+        .run(move |conn| {
+            conn.execute(
+                "UPDATE conversations SET archived = ? WHERE id = ?",
+                params![if archived { 1 } else { 0 }, id],
+            )?;
+            Ok(())
+        })
+        .await
 }
 
 #[tauri::command]
@@ -247,32 +343,21 @@ pub async fn db_update_conversation_title(
     app: tauri::AppHandle,
     id: String,
     title: String,
-) -> Result<Conversation, String> {
-    let conn = get_conn(&app)?;
-    conn.execute(
-        "UPDATE conversations SET title = ? WHERE id = ?",
-        params![title, id],
-    )
-    .map_err(|e| e.to_string())?;
-    let mut stmt = conn
-        .prepare("SELECT id, title, created_at, archived, model FROM conversations WHERE id = ?")
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt.query(params![id]).map_err(|e| e.to_string())?;
-    if let Some(row) = rows.next().map_err(|e| e.to_string())? {
-        let id_v: String = row.get(0).map_err(|e| e.to_string())?;
-        let title_v: String = row.get(1).map_err(|e| e.to_string())?;
-        let created_v: String = row.get(2).map_err(|e| e.to_string())?;
-        let archived_v: i64 = row.get(3).map_err(|e| e.to_string())?;
-        let model_v: Option<String> = row.get(4).map_err(|e| e.to_string())?;
-        return Ok(Conversation {
-            id: id_v,
-            title: title_v,
-            created_at: created_v,
-            archived: archived_v,
-            model: model_v,
-        });
-    }
-    Err("conversation not found".into())
+) -> AppResult<Conversation> {
+    app.state::<Database>()
+        .run(move |conn| {
+            let tx = conn.transaction()?;
+            let updated = tx.execute(
+                "UPDATE conversations SET title = ? WHERE id = ?",
+                params![title, id],
+            )?;
+            if updated > 0 {
+                search::index_title(&tx, &id, &title)?;
+            }
+            tx.commit()?;
+            query_conversation(conn, &id)
+        })
+        .await
 }
 
 // db_generate_conversation_title has been removed - frontend handles title generation with AI SDK
@@ -282,92 +367,125 @@ pub async fn db_update_conversation_model(
     app: tauri::AppHandle,
     conversation_id: String,
     model: String,
-) -> Result<(), String> {
-    let conn = get_conn(&app)?;
-    conn.execute(
-        "UPDATE conversations SET model = ? WHERE id = ?",
-        params![model, conversation_id],
-    )
-    .map_err(|e| e.to_string())?;
-    Ok(())
+) -> AppResult<()> {
+    app.state::<Database>()
+        .run(move |conn| {
+            conn.execute(
+                "UPDATE conversations SET model = ? WHERE id = ?",
+                params![model, conversation_id],
+            )?;
+            Ok(())
+        })
+        .await
 }
 
 #[tauri::command]
-pub async fn db_get_conversation(
+pub async fn db_get_conversation_options(
     app: tauri::AppHandle,
     conversation_id: String,
-) -> Result<Conversation, String> {
-    let conn = get_conn(&app)?;
-    let mut stmt = conn
-        .prepare("SELECT id, title, created_at, archived, model FROM conversations WHERE id = ?")
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt
-        .query(params![conversation_id])
-        .map_err(|e| e.to_string())?;
-    if let Some(row) = rows.next().map_err(|e| e.to_string())? {
-        let id: String = row.get(0).map_err(|e| e.to_string())?;
-        let title: String = row.get(1).map_err(|e| e.to_string())?;
-        let created_at: String = row.get(2).map_err(|e| e.to_string())?;
-        let archived: i64 = row.get(3).map_err(|e| e.to_string())?;
-        let model: Option<String> = row.get(4).map_err(|e| e.to_string())?;
-        return Ok(Conversation {
-            id,
-            title,
-            created_at,
-            archived,
-            model,
-        });
-    }
-    Err("conversation not found".into())
+) -> AppResult<Option<GenerationOptions>> {
+    read_conversation_options(&app, &conversation_id).await
 }
 
-// AI SDK compatible functions
 #[tauri::command]
-pub async fn db_get_ai_messages(
+pub async fn db_update_conversation_options(
     app: tauri::AppHandle,
     conversation_id: String,
-) -> Result<Vec<serde_json::Value>, String> {
-    let conn = get_conn(&app)?;
-
-    // First, check total message count in database
-    let mut count_stmt = conn
-        .prepare("SELECT COUNT(*) FROM messages WHERE conversation_id = ?")
-        .map_err(|e| e.to_string())?;
-    let _count: i64 = count_stmt
-        .query_row([conversation_id.clone()], |row| row.get(0))
-        .map_err(|e| e.to_string())?;
-
-    let mut stmt = conn.prepare("SELECT content FROM messages WHERE conversation_id = ? ORDER BY datetime(created_at) ASC").map_err(|e| e.to_string())?;
-
-    let mut messages = Vec::new();
-    let rows = stmt
-        .query_map([conversation_id.clone()], |row| {
-            let content_str: String = row.get(0)?;
-            Ok(content_str)
+    options: Option<GenerationOptions>,
+) -> AppResult<()> {
+    let options_json = match options.filter(|o| !o.is_empty()) {
+        Some(options) => {
+            Some(serde_json::to_string(&options).context("Failed to serialize options")?)
+        }
+        None => None,
+    };
+    app.state::<Database>()
+        .run(move |conn| {
+            conn.execute(
+                "UPDATE conversations SET options = ? WHERE id = ?",
+                params![options_json, conversation_id],
+            )?;
+            Ok(())
         })
-        .map_err(|e| e.to_string())?;
+        .await
+}
 
-    for row_result in rows {
-        let content_str = row_result.map_err(|e| e.to_string())?;
+#[tauri::command]
+pub async fn db_get_conversation(
+    app: tauri::AppHandle,
+    conversation_id: String,
+) -> AppResult<Conversation> {
+    app.state::<Database>()
+        .run(move |conn| query_conversation(conn, &conversation_id))
+        .await
+}
 
-        // Parse as JSON (AI SDK message format)
-        match serde_json::from_str::<serde_json::Value>(&content_str) {
-            Ok(json_message) => {
-                messages.push(json_message);
-            }
-            Err(_e) => {}
-        }
-    }
+// AI SDK compatible functions
 
-    Ok(messages)
+/// A page of a conversation's messages, oldest first. Without a cursor the latest
+/// messages are returned; page back through older ones with `before`.
+#[tauri::command]
+pub async fn db_get_ai_messages(
+    app: tauri::AppHandle,
+    conversation_id: String,
+    page: Option<PageInput>,
+) -> AppResult<Page<serde_json::Value>> {
+    let request = PageRequest::new(page.unwrap_or_default())?;
+    app.state::<Database>()
+        .run(move |conn| {
+            let mut stmt = conn.prepare(&format!(
+                r#"
+                SELECT id, created_at, content FROM messages
+                WHERE conversation_id = ?1
+                  AND (?2 IS NULL OR (julianday(created_at), id) < (julianday(?2), ?3))
+                  AND (?4 IS NULL OR (julianday(created_at), id) > (julianday(?4), ?5))
+                ORDER BY julianday(created_at) {order}, id {order}
+                LIMIT ?6
+                "#,
+                order = request.order()
+            ))?;
+
+            let rows = stmt.query_map(
+                params![
+                    conversation_id,
+                    request.before.as_ref().map(|c| &c.at),
+                    request.before.as_ref().map(|c| &c.id),
+                    request.after.as_ref().map(|c| &c.at),
+                    request.after.as_ref().map(|c| &c.id),
+                    request.fetch_limit()
+                ],
+                |row| {
+                    let id: String = row.get(0)?;
+                    let created_at: String = row.get(1)?;
+                    let content: String = row.get(2)?;
+                    Ok((Cursor::encode(&created_at, &id), content))
+                },
+            )?;
+            let mut out = Vec::new();
+            for r in rows {
+                out.push(r?);
+            }
+            let page = request.page(out, false);
+
+            // Parse as JSON (AI SDK message format)
+            Ok(Page {
+                items: page
+                    .items
+                    .iter()
+                    .filter_map(|content| serde_json::from_str(content).ok())
+                    .collect(),
+                older_cursor: page.older_cursor,
+                newer_cursor: page.newer_cursor,
+            })
+        })
+        .await
 }
 
 #[tauri::command]
 pub async fn db_add_ai_message(
     app: tauri::AppHandle,
     input: AddAIMessageInput,
-) -> Result<AIMessage, String> {
-    let conn = get_conn(&app)?;
+) -> AppResult<AIMessage> {
     let id = uuid().to_string();
     let created_at = Utc::now().to_rfc3339();
 
@@ -396,13 +514,28 @@ pub async fn db_add_ai_message(
     };
 
     // Serialize the full message structure
-    let message_json = serde_json::to_string(&message)
-        .map_err(|e| format!("Failed to serialize message: {}", e))?;
-
-    conn.execute(
-        "INSERT INTO messages (id, conversation_id, role, content, created_at) VALUES (?, ?, ?, ?, ?)",
-        params![id, input.conversation_id, input.role, message_json, created_at],
-    ).map_err(|e| e.to_string())?;
+    let message_json = serde_json::to_string(&message).context("Failed to serialize message")?;
+
+    app.state::<Database>()
+        .run(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "INSERT INTO messages (id, conversation_id, role, content, created_at, preview, tokens) VALUES (?, ?, ?, ?, ?, ?, ?)",
+                params![
+                    id,
+                    input.conversation_id,
+                    input.role,
+                    message_json,
+                    created_at,
+                    stats::message_preview(&message_json),
+                    stats::message_tokens(&message_json)
+                ],
+            )?;
+            search::index_message(&tx, &id, &input.conversation_id, &message_json)?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await?;
 
     Ok(message)
 }
@@ -411,9 +544,7 @@ pub async fn db_add_ai_message(
 pub async fn db_save_complete_message(
     app: tauri::AppHandle,
     input: SaveCompleteMessageInput,
-) -> Result<(), String> {
-    let conn = get_conn(&app)?;
-
+) -> AppResult<()> {
     // Add conversation_id and created_at to the message if not present
     let mut message_with_meta = input.message.clone();
     if !message_with_meta["conversation_id"].is_string() {
@@ -426,22 +557,43 @@ pub async fn db_save_complete_message(
     }
 
     // Store the complete message as JSON
-    let message_json = serde_json::to_string(&message_with_meta)
-        .map_err(|e| format!("Failed to serialize message: {}", e))?;
+    let message_json =
+        serde_json::to_string(&message_with_meta).context("Failed to serialize message")?;
 
     // Extract values for database insertion
-    let message_id = message_with_meta["id"].as_str().unwrap_or("unknown");
-    let role = message_with_meta["role"].as_str().unwrap_or("unknown");
-    let default_created_at = chrono::Utc::now().to_rfc3339();
+    let message_id = message_with_meta["id"]
+        .as_str()
+        .unwrap_or("unknown")
+        .to_string();
+    let role = message_with_meta["role"]
+        .as_str()
+        .unwrap_or("unknown")
+        .to_string();
     let created_at = message_with_meta["created_at"]
         .as_str()
-        .unwrap_or(&default_created_at);
+        .map(str::to_string)
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
 
     // Insert or update the message (upsert)
-    conn.execute(
-        "INSERT OR REPLACE INTO messages (id, conversation_id, role, content, created_at) VALUES (?, ?, ?, ?, ?)",
-        params![message_id, input.conversation_id, role, message_json, created_at],
-    ).map_err(|e| format!("Failed to save message: {}", e))?;
-
-    Ok(())
+    app.state::<Database>()
+        .run(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "INSERT OR REPLACE INTO messages (id, conversation_id, role, content, created_at, preview, tokens) VALUES (?, ?, ?, ?, ?, ?, ?)",
+                params![
+                    message_id,
+                    input.conversation_id,
+                    role,
+                    message_json,
+                    created_at,
+                    stats::message_preview(&message_json),
+                    stats::message_tokens(&message_json)
+                ],
+            )
+            .context("Failed to save message")?;
+            search::index_message(&tx, &message_id, &input.conversation_id, &message_json)?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await
 }
@@ -0,0 +1,210 @@
+use crate::modules::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 500;
+
+/// Which page of a time-ordered list to return. Without a cursor the newest items are
+/// returned; `before` and `after` take a cursor from a previous [`Page`].
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PageInput {
+    pub limit: Option<u32>,
+    pub before: Option<String>, // Items older than this cursor
+    pub after: Option<String>,  // Items newer than this cursor
+}
+
+/// One page of a list, with cursors for the pages on either side.
+#[derive(Debug, Clone, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub older_cursor: Option<String>, // Pass as `before`; None when there is nothing older
+    pub newer_cursor: Option<String>, // Pass as `after`; None when there is nothing newer
+}
+
+/// Position in a list ordered by timestamp, with the id breaking ties.
+#[derive(Debug, Clone)]
+pub struct Cursor {
+    pub at: String,
+    pub id: String,
+}
+
+impl Cursor {
+    pub fn encode(at: &str, id: &str) -> String {
+        format!("{}|{}", at, id)
+    }
+
+    fn decode(cursor: &str) -> AppResult<Self> {
+        let (at, id) = cursor
+            .split_once('|')
+            .ok_or_else(|| AppError::invalid_input(format!("invalid cursor: {}", cursor)))?;
+        Ok(Self {
+            at: at.to_string(),
+            id: id.to_string(),
+        })
+    }
+}
+
+/// A validated [`PageInput`].
+pub struct PageRequest {
+    pub limit: u32,
+    pub before: Option<Cursor>,
+    pub after: Option<Cursor>,
+}
+
+impl PageRequest {
+    pub fn new(input: PageInput) -> AppResult<Self> {
+        if input.before.is_some() && input.after.is_some() {
+            return Err(AppError::invalid_input(
+                "pass either a before or an after cursor, not both",
+            ));
+        }
+        Ok(Self {
+            limit: input.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+            before: input.before.as_deref().map(Cursor::decode).transpose()?,
+            after: input.after.as_deref().map(Cursor::decode).transpose()?,
+        })
+    }
+
+    /// Rows are read moving away from the cursor: oldest first after an `after` cursor,
+    /// newest first otherwise.
+    pub fn ascending(&self) -> bool {
+        self.after.is_some()
+    }
+
+    /// `ORDER BY` direction for [`Self::ascending`].
+    pub fn order(&self) -> &'static str {
+        if self.ascending() {
+            "ASC"
+        } else {
+            "DESC"
+        }
+    }
+
+    /// Rows to fetch: one more than the limit tells whether another page follows.
+    pub fn fetch_limit(&self) -> u32 {
+        self.limit + 1
+    }
+
+    /// Build the page from `(cursor, item)` rows fetched in [`Self::order`], returning
+    /// the items newest first or oldest first.
+    pub fn page<T>(&self, mut rows: Vec<(String, T)>, newest_first: bool) -> Page<T> {
+        let has_more = rows.len() > self.limit as usize;
+        rows.truncate(self.limit as usize);
+
+        let beyond = if has_more {
+            rows.last().map(|(cursor, _)| cursor.clone())
+        } else {
+            None
+        };
+        // A cursor was given, so there are items on its other side
+        let behind = match (&self.before, &self.after) {
+            (None, None) => None,
+            _ => rows.first().map(|(cursor, _)| cursor.clone()),
+        };
+        let (older_cursor, newer_cursor) = if self.ascending() {
+            (behind, beyond)
+        } else {
+            (beyond, behind)
+        };
+
+        if self.ascending() == newest_first {
+            rows.reverse();
+        }
+        Page {
+            items: rows.into_iter().map(|(_, item)| item).collect(),
+            older_cursor,
+            newer_cursor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::error::ErrorKind;
+
+    fn request(limit: u32, before: Option<&str>, after: Option<&str>) -> AppResult<PageRequest> {
+        PageRequest::new(PageInput {
+            limit: Some(limit),
+            before: before.map(str::to_string),
+            after: after.map(str::to_string),
+        })
+    }
+
+    fn request_error(input: PageInput) -> AppError {
+        PageRequest::new(input).err().unwrap()
+    }
+
+    fn rows(ids: &[u32]) -> Vec<(String, u32)> {
+        ids.iter()
+            .map(|id| (Cursor::encode("2024-01-01T00:00:00Z", &id.to_string()), *id))
+            .collect()
+    }
+
+    #[test]
+    fn decodes_cursors() {
+        let request = request(10, Some("2024-01-01T00:00:00Z|a|b"), None).unwrap();
+        let cursor = request.before.unwrap();
+        assert_eq!(cursor.at, "2024-01-01T00:00:00Z");
+        assert_eq!(cursor.id, "a|b");
+
+        let error = request_error(PageInput {
+            before: Some("no-separator".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(error.kind, ErrorKind::InvalidInput);
+        let error = request_error(PageInput {
+            before: Some("a|1".to_string()),
+            after: Some("b|2".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(error.kind, ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn clamps_the_limit() {
+        let default = PageRequest::new(PageInput::default()).unwrap();
+        assert_eq!(default.limit, DEFAULT_LIMIT);
+        assert_eq!(request(0, None, None).unwrap().limit, 1);
+        assert_eq!(request(10_000, None, None).unwrap().limit, MAX_LIMIT);
+    }
+
+    #[test]
+    fn pages_backwards_from_the_newest() {
+        let first = request(2, None, None).unwrap();
+        assert_eq!(first.order(), "DESC");
+        // Fetched newest first, one more than the limit
+        let page = first.page(rows(&[5, 4, 3]), false);
+        assert_eq!(page.items, [4, 5]);
+        assert_eq!(
+            page.older_cursor,
+            Some(Cursor::encode("2024-01-01T00:00:00Z", "4"))
+        );
+        assert_eq!(page.newer_cursor, None);
+
+        let older = request(2, page.older_cursor.as_deref(), None).unwrap();
+        let page = older.page(rows(&[3, 2]), true);
+        assert_eq!(page.items, [3, 2]);
+        assert_eq!(page.older_cursor, None);
+        assert_eq!(
+            page.newer_cursor,
+            Some(Cursor::encode("2024-01-01T00:00:00Z", "3"))
+        );
+    }
+
+    #[test]
+    fn pages_forwards_after_a_cursor() {
+        let newer = request(2, None, Some("2024-01-01T00:00:00Z|2")).unwrap();
+        assert_eq!(newer.order(), "ASC");
+        let page = newer.page(rows(&[3, 4, 5]), true);
+        assert_eq!(page.items, [4, 3]);
+        assert_eq!(
+            page.older_cursor,
+            Some(Cursor::encode("2024-01-01T00:00:00Z", "3"))
+        );
+        assert_eq!(
+            page.newer_cursor,
+            Some(Cursor::encode("2024-01-01T00:00:00Z", "4"))
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use super::migrations;
+use crate::modules::error::{AppResult, Context};
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Connections kept open between commands; more are opened when they are all busy.
+const MAX_IDLE: usize = 4;
+
+/// How long a connection waits on another one's write lock before failing.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pool of `app.db` connections, held in Tauri managed state.
+///
+/// Work runs on the blocking thread pool through [`Database::run`], so SQLite never
+/// blocks the async runtime.
+pub struct Database(Arc<Pool>);
+
+struct Pool {
+    path: PathBuf,
+    idle: Mutex<Vec<Connection>>,
+}
+
+impl Database {
+    /// Open the database at `path` and migrate it to the current schema.
+    pub fn open(path: PathBuf) -> AppResult<Self> {
+        let mut conn = open_connection(&path)?;
+        migrations::migrate(&mut conn, &path)?;
+        Ok(Self(Arc::new(Pool {
+            path,
+            idle: Mutex::new(vec![conn]),
+        })))
+    }
+
+    /// Run `f` with a pooled connection on a blocking thread.
+    pub async fn run<T, F>(&self, f: F) -> AppResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> AppResult<T> + Send + 'static,
+    {
+        let pool = self.0.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let mut conn = pool.acquire()?;
+            let result = f(&mut conn);
+            pool.release(conn);
+            result
+        })
+        .await?
+    }
+}
+
+impl Pool {
+    fn acquire(&self) -> AppResult<Connection> {
+        if let Some(conn) = self.idle.lock()?.pop() {
+            return Ok(conn);
+        }
+        open_connection(&self.path)
+    }
+
+    fn release(&self, conn: Connection) {
+        // An unfinished transaction would leak into the next command
+        if !conn.is_autocommit() {
+            return;
+        }
+        if let Ok(mut idle) = self.idle.lock() {
+            if idle.len() < MAX_IDLE {
+                idle.push(conn);
+            }
+        }
+    }
+}
+
+fn open_connection(path: &Path) -> AppResult<Connection> {
+    let conn = Connection::open(path).context("open db")?;
+    conn.busy_timeout(BUSY_TIMEOUT).context("open db")?;
+    // foreign_keys is off by default and per connection; without it ON DELETE CASCADE
+    // does nothing
+    conn.execute_batch(
+        r#"
+        PRAGMA journal_mode = WAL;
+        PRAGMA foreign_keys = ON;
+        "#,
+    )
+    .context("open db")?;
+    Ok(conn)
+}
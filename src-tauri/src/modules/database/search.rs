@@ -0,0 +1,216 @@
+use super::Database;
+use crate::modules::error::{AppError, AppResult};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 200;
+// Private-use characters FTS5 puts around matches, swapped for <mark> once the text is escaped
+const MATCH_START: &str = "\u{E000}";
+const MATCH_END: &str = "\u{E001}";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchInput {
+    pub query: String,
+    pub model: Option<String>,
+    pub from: Option<String>, // RFC 3339, inclusive
+    pub to: Option<String>,   // RFC 3339, inclusive
+    pub archived: Option<bool>,
+    pub limit: Option<u32>,
+}
+
+/// A message or conversation title matching a search, best match first.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub conversation_id: String,
+    pub message_id: Option<String>, // None when the conversation title matched
+    pub conversation_title: String,
+    pub snippet: String, // HTML-escaped, with matched terms wrapped in <mark></mark>
+    pub rank: f64,       // bm25, lower is better
+    pub created_at: String,
+    pub model: Option<String>,
+    pub archived: i64,
+}
+
+/// Searchable text of a stored message: the text parts of an AI SDK message, the
+/// `content` of an older JSON message, or the stored string itself.
+pub fn message_text(content: &str) -> String {
+    let Ok(message) = serde_json::from_str::<serde_json::Value>(content) else {
+        return content.to_string();
+    };
+    if let Some(parts) = message["parts"].as_array() {
+        return parts
+            .iter()
+            .filter(|part| part["type"] == "text")
+            .filter_map(|part| part["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    match &message["content"] {
+        serde_json::Value::String(text) => text.clone(),
+        _ => content.to_string(),
+    }
+}
+
+/// Replace the index entry for a message. Entries of deleted messages and conversations
+/// are removed through `search_entries`' cascades and trigger.
+pub fn index_message(
+    conn: &Connection,
+    message_id: &str,
+    conversation_id: &str,
+    content: &str,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM search_entries WHERE message_id = ?",
+        params![message_id],
+    )?;
+    conn.execute(
+        "INSERT INTO search_entries (conversation_id, message_id) VALUES (?, ?)",
+        params![conversation_id, message_id],
+    )?;
+    insert_text(conn, &message_text(content))
+}
+
+/// Replace the index entry for a conversation title.
+pub fn index_title(conn: &Connection, conversation_id: &str, title: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM search_entries WHERE conversation_id = ? AND message_id IS NULL",
+        params![conversation_id],
+    )?;
+    conn.execute(
+        "INSERT INTO search_entries (conversation_id, message_id) VALUES (?, NULL)",
+        params![conversation_id],
+    )?;
+    insert_text(conn, title)
+}
+
+/// Index `text` under the entry just inserted.
+fn insert_text(conn: &Connection, text: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO search_index (rowid, text) VALUES (?, ?)",
+        params![conn.last_insert_rowid(), text],
+    )?;
+    Ok(())
+}
+
+/// Escape a snippet for HTML and wrap its matches in `<mark>`, so message text can never
+/// inject markup into the results.
+fn highlight(snippet: &str) -> String {
+    let mut out = String::with_capacity(snippet.len());
+    for c in snippet.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out.replace(MATCH_START, "<mark>")
+        .replace(MATCH_END, "</mark>")
+}
+
+/// Turn user input into an FTS5 query: every word must appear, the last one as a prefix
+/// so results update while typing. Quoting keeps FTS5 syntax characters literal.
+fn match_expression(query: &str) -> Option<String> {
+    let words: Vec<String> = query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    Some(format!("{}*", words.join(" ")))
+}
+
+/// Full-text search over message text and conversation titles.
+#[tauri::command]
+pub async fn db_search(app: tauri::AppHandle, input: SearchInput) -> AppResult<Vec<SearchHit>> {
+    let expression = match_expression(&input.query)
+        .ok_or_else(|| AppError::invalid_input("search query is empty"))?;
+    let limit = input.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    app.state::<Database>()
+        .run(move |conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT e.conversation_id, e.message_id, c.title,
+                       snippet(search_index, 0, ?7, ?8, '…', 16),
+                       bm25(search_index) AS rank,
+                       COALESCE(m.created_at, c.created_at), c.model, c.archived
+                FROM search_index
+                JOIN search_entries e ON e.id = search_index.rowid
+                JOIN conversations c ON c.id = e.conversation_id
+                LEFT JOIN messages m ON m.id = e.message_id
+                WHERE search_index MATCH ?1
+                  AND (?2 IS NULL OR c.model = ?2)
+                  AND (?3 IS NULL OR c.archived = ?3)
+                  AND (?4 IS NULL OR datetime(COALESCE(m.created_at, c.created_at)) >= datetime(?4))
+                  AND (?5 IS NULL OR datetime(COALESCE(m.created_at, c.created_at)) <= datetime(?5))
+                ORDER BY rank
+                LIMIT ?6
+                "#,
+            )?;
+            let rows = stmt.query_map(
+                params![
+                    expression,
+                    input.model,
+                    input.archived.map(i64::from),
+                    input.from,
+                    input.to,
+                    limit,
+                    MATCH_START,
+                    MATCH_END
+                ],
+                |row| {
+                    Ok(SearchHit {
+                        conversation_id: row.get(0)?,
+                        message_id: row.get(1)?,
+                        conversation_title: row.get(2)?,
+                        snippet: highlight(&row.get::<_, String>(3)?),
+                        rank: row.get(4)?,
+                        created_at: row.get(5)?,
+                        model: row.get(6)?,
+                        archived: row.get(7)?,
+                    })
+                },
+            )?;
+            let mut out = Vec::new();
+            for r in rows {
+                out.push(r?);
+            }
+            Ok(out)
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_snippets_before_marking_matches() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE VIRTUAL TABLE search_index USING fts5(text)")
+            .unwrap();
+        conn.execute(
+            "INSERT INTO search_index (text) VALUES (?)",
+            params![r#"Try <img src=x onerror="alert(1)"> & fish"#],
+        )
+        .unwrap();
+
+        let snippet: String = conn
+            .query_row(
+                "SELECT snippet(search_index, 0, ?2, ?3, '…', 16) FROM search_index WHERE search_index MATCH ?1",
+                params![match_expression("fish").unwrap(), MATCH_START, MATCH_END],
+                |row| row.get(0),
+            )
+            .unwrap();
+
+        assert_eq!(
+            highlight(&snippet),
+            "Try &lt;img src=x onerror=&quot;alert(1)&quot;&gt; &amp; <mark>fish</mark>"
+        );
+    }
+}
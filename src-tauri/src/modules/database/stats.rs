@@ -0,0 +1,40 @@
+use super::search::message_text;
+
+/// Longest preview kept for a message, in characters.
+const PREVIEW_CHARS: usize = 160;
+
+/// Single-line start of a message's text, shown under the conversation title.
+pub fn message_preview(content: &str) -> String {
+    let text = message_text(content);
+    let preview = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if preview.chars().count() <= PREVIEW_CHARS {
+        return preview;
+    }
+    let mut preview: String = preview.chars().take(PREVIEW_CHARS - 1).collect();
+    preview.push('…');
+    preview
+}
+
+/// Tokens recorded in a message's `metadata.usage`, as the AI SDK reports them or as
+/// `chat_stream_end` does. 0 when the message has no usage.
+pub fn message_tokens(content: &str) -> i64 {
+    let Ok(message) = serde_json::from_str::<serde_json::Value>(content) else {
+        return 0;
+    };
+    let usage = &message["metadata"]["usage"];
+    if let Some(total) = usage["totalTokens"]
+        .as_i64()
+        .or_else(|| usage["total_tokens"].as_i64())
+    {
+        return total;
+    }
+    [
+        "inputTokens",
+        "outputTokens",
+        "input_tokens",
+        "output_tokens",
+    ]
+    .iter()
+    .filter_map(|key| usage[key].as_i64())
+    .sum()
+}
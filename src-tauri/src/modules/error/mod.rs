@@ -0,0 +1,139 @@
+use serde::Serialize;
+use std::fmt;
+
+/// What went wrong, so the frontend can react without parsing messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    Database,
+    Io,
+    Serialization,
+    InvalidInput,
+    NotFound,
+    NotConfigured, // e.g. a missing API key
+    Auth,          // The provider rejected our credentials
+    RateLimited,
+    Network, // The provider could not be reached or timed out
+    Provider,
+    Cancelled,
+    Internal,
+}
+
+impl ErrorKind {
+    /// Whether trying again unchanged may succeed.
+    pub fn is_retryable(self) -> bool {
+        matches!(self, ErrorKind::Network | ErrorKind::RateLimited)
+    }
+}
+
+/// Error returned by every command.
+#[derive(Debug, Clone, Serialize)]
+pub struct AppError {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub retryable: bool,
+    pub provider: Option<String>, // Adapter kind of the provider that failed
+    pub details: Option<String>,  // Underlying error, when the message summarizes it
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            retryable: kind.is_retryable(),
+            provider: None,
+            details: None,
+        }
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::InvalidInput, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::NotFound, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Internal, message)
+    }
+
+    /// A failure reported by or about a provider.
+    pub fn provider(kind: ErrorKind, provider: &str, message: impl Into<String>) -> Self {
+        Self::new(kind, message).with_provider(provider)
+    }
+
+    pub fn with_provider(mut self, provider: impl Into<String>) -> Self {
+        self.provider = Some(provider.into());
+        self
+    }
+
+    pub fn with_details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+
+    pub fn with_retryable(mut self, retryable: bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        match e {
+            rusqlite::Error::QueryReturnedNoRows => Self::not_found("record not found"),
+            e => Self::new(ErrorKind::Database, e.to_string()),
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        Self::new(ErrorKind::Io, e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::new(ErrorKind::Serialization, e.to_string())
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for AppError {
+    fn from(_: std::sync::PoisonError<T>) -> Self {
+        Self::internal("lock poisoned")
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(e: tauri::Error) -> Self {
+        Self::internal(e.to_string())
+    }
+}
+
+/// Prefix an error with what was being done, keeping its kind.
+pub trait Context<T> {
+    fn context(self, action: &str) -> AppResult<T>;
+}
+
+impl<T, E: Into<AppError>> Context<T> for Result<T, E> {
+    fn context(self, action: &str) -> AppResult<T> {
+        self.map_err(|e| {
+            let mut e = e.into();
+            e.message = format!("{}: {}", action, e.message);
+            e
+        })
+    }
+}
@@ -1,5 +1,7 @@
 pub mod chat;
 pub mod database;
+pub mod error;
+pub mod models;
 pub mod providers;
 pub mod settings;
 pub mod system;
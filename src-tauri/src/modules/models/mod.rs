@@ -0,0 +1,216 @@
+use crate::modules::chat::{discover_chat_models, ollama_provider, ModelRefresh};
+use crate::modules::error::{AppError, AppResult, ErrorKind};
+use crate::modules::providers::{parse_modelfile, Modelfile, OllamaProvider, RunningModel};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+
+/// Model downloads currently in progress, keyed by model name.
+#[derive(Default)]
+pub struct ActivePulls(pub Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>>);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PullProgress {
+    pub model: String,
+    pub status: String,
+    pub digest: Option<String>, // Layer being downloaded, if any
+    pub total: Option<u64>,
+    pub completed: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PullEnd {
+    pub model: String,
+    pub status: String, // "success" | "error" | "cancelled"
+    pub error: Option<AppError>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateModelInput {
+    pub name: String,
+    pub modelfile: Option<String>, // Modelfile text; the fields below override it
+    pub from: Option<String>,
+    pub system: Option<String>,
+}
+
+/// Re-list Ollama's models and push the full list to the webview as `chat_models_updated`.
+async fn emit_models_updated(app: &tauri::AppHandle) {
+    if let Ok(models) = discover_chat_models(app, ModelRefresh::Only("Ollama".into())).await {
+        let _ = app.emit("chat_models_updated", models);
+    }
+}
+
+/// Start downloading an Ollama model in the background.
+///
+/// Progress is emitted as `ollama_pull_progress` events and the download finishes with an
+/// `ollama_pull_end` event, which also carries the error if the pull could not start. On
+/// success the refreshed model list is emitted as `chat_models_updated`.
+#[tauri::command]
+pub async fn pull_ollama_model(
+    app: tauri::AppHandle,
+    model: String,
+    insecure: Option<bool>,
+) -> AppResult<()> {
+    let model = model.trim().to_string();
+    if model.is_empty() {
+        return Err(AppError::invalid_input("Model name is required"));
+    }
+    let provider = ollama_provider(&app)?;
+
+    // Check and register under one lock so two requests cannot start the same pull
+    let pulls = app.state::<ActivePulls>();
+    let mut active = pulls.0.lock()?;
+    if active.contains_key(&model) {
+        return Err(AppError::invalid_input(format!(
+            "{} is already being downloaded",
+            model
+        )));
+    }
+
+    let task = tauri::async_runtime::spawn({
+        let app = app.clone();
+        let model = model.clone();
+        async move {
+            let error = pull(&app, &provider, &model, insecure.unwrap_or(false))
+                .await
+                .err();
+
+            if let Ok(mut active) = app.state::<ActivePulls>().0.lock() {
+                active.remove(&model);
+            }
+
+            let succeeded = error.is_none();
+            let _ = app.emit(
+                "ollama_pull_end",
+                PullEnd {
+                    model,
+                    status: if succeeded { "success" } else { "error" }.to_string(),
+                    error,
+                },
+            );
+
+            if succeeded {
+                emit_models_updated(&app).await;
+            }
+        }
+    });
+    active.insert(model, task);
+
+    Ok(())
+}
+
+/// Run a pull to the end, emitting each progress line as `ollama_pull_progress`.
+async fn pull(
+    app: &tauri::AppHandle,
+    provider: &OllamaProvider,
+    model: &str,
+    insecure: bool,
+) -> AppResult<()> {
+    let mut stream = provider.pull_model(model, insecure).await?;
+    while let Some(status) = stream.next().await {
+        let status = status?;
+        let _ = app.emit(
+            "ollama_pull_progress",
+            PullProgress {
+                model: model.to_string(),
+                status: status.status,
+                digest: status.digest,
+                total: status.total,
+                completed: status.completed,
+            },
+        );
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn cancel_ollama_pull(app: tauri::AppHandle, model: String) -> AppResult<bool> {
+    let task = app.state::<ActivePulls>().0.lock()?.remove(&model);
+
+    let Some(task) = task else {
+        return Ok(false);
+    };
+    // Dropping the task drops the response stream, which aborts the download
+    task.abort();
+    let _ = app.emit(
+        "ollama_pull_end",
+        PullEnd {
+            error: Some(AppError::provider(
+                ErrorKind::Cancelled,
+                "Ollama",
+                format!("Pull of {} was cancelled", model),
+            )),
+            model,
+            status: "cancelled".to_string(),
+        },
+    );
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn delete_ollama_model(app: tauri::AppHandle, model: String) -> AppResult<()> {
+    let provider = ollama_provider(&app)?;
+    provider.delete_model(&model).await?;
+    emit_models_updated(&app).await;
+    Ok(())
+}
+
+/// Copy a local model under a new name; with `rename` the source is removed afterwards.
+#[tauri::command]
+pub async fn copy_ollama_model(
+    app: tauri::AppHandle,
+    source: String,
+    destination: String,
+    rename: Option<bool>,
+) -> AppResult<()> {
+    let destination = destination.trim();
+    if destination.is_empty() {
+        return Err(AppError::invalid_input(
+            "Destination model name is required",
+        ));
+    }
+
+    let provider = ollama_provider(&app)?;
+    provider.copy_model(&source, destination).await?;
+    if rename.unwrap_or(false) && source != destination {
+        provider.delete_model(&source).await?;
+    }
+    emit_models_updated(&app).await;
+    Ok(())
+}
+
+/// Create a custom model (e.g. a base model with its own system prompt and parameters).
+#[tauri::command]
+pub async fn create_ollama_model(
+    app: tauri::AppHandle,
+    input: CreateModelInput,
+) -> AppResult<String> {
+    let name = input.name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid_input("Model name is required"));
+    }
+
+    let mut modelfile = match input.modelfile.as_deref() {
+        Some(text) => parse_modelfile(text)?,
+        None => Modelfile::default(),
+    };
+    if input.from.is_some() {
+        modelfile.from = input.from;
+    }
+    if input.system.is_some() {
+        modelfile.system = input.system;
+    }
+
+    let provider = ollama_provider(&app)?;
+    let status = provider.create_model(name, modelfile).await?;
+    emit_models_updated(&app).await;
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn list_running_ollama_models(app: tauri::AppHandle) -> AppResult<Vec<RunningModel>> {
+    let provider = ollama_provider(&app)?;
+    provider.list_running_models().await
+}
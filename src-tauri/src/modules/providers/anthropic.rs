@@ -0,0 +1,579 @@
+use super::http::{describe_request_error, error_from_response};
+use super::messages::{normalize_messages, Content, ImageData, Message, Role};
+use super::provider::Provider;
+use super::sse::sse_events;
+use super::types::{
+    parse_tool_arguments, ChatEvent, ChatRequest, ChatStream, ModelCapabilities, ModelInfo,
+    ToolCall, ToolDefinition, Usage,
+};
+use crate::modules::error::{AppError, AppResult, ErrorKind};
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+pub const DEFAULT_ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com/v1";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+// The Messages API requires max_tokens; used when the options do not set num_predict
+const DEFAULT_MAX_TOKENS: u32 = 8192;
+
+/// A client for the Anthropic Messages API.
+#[derive(Clone)]
+pub struct AnthropicProvider {
+    base_url: String,
+    api_key: Option<String>,
+    http: reqwest::Client,
+}
+
+#[derive(Serialize)]
+struct MessagesRequest<'a> {
+    model: &'a str,
+    max_tokens: u32,
+    messages: Vec<Value>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    system: String,
+    stream: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_k: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<&'a [String]>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelsResponse {
+    #[serde(default)]
+    data: Vec<Value>,
+}
+
+/// A `tool_use` block whose JSON input is still streaming in.
+struct PendingToolUse {
+    id: String,
+    name: String,
+    input: String,
+}
+
+impl AnthropicProvider {
+    pub fn new(base_url: Option<&str>, api_key: Option<&str>) -> AppResult<Self> {
+        let base_url = base_url
+            .unwrap_or(DEFAULT_ANTHROPIC_BASE_URL)
+            .trim()
+            .trim_end_matches('/');
+        reqwest::Url::parse(base_url).map_err(|e| {
+            AppError::invalid_input(format!("Invalid Anthropic URL '{}': {}", base_url, e))
+                .with_provider("Anthropic")
+        })?;
+        Ok(Self {
+            base_url: base_url.to_string(),
+            api_key: api_key
+                .filter(|api_key| !api_key.trim().is_empty())
+                .map(str::to_string),
+            http: reqwest::Client::new(),
+        })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> AppResult<reqwest::RequestBuilder> {
+        let api_key = self.api_key.as_deref().ok_or_else(|| {
+            AppError::provider(
+                ErrorKind::NotConfigured,
+                "Anthropic",
+                "Anthropic API key not found. Please configure it in settings.",
+            )
+        })?;
+        Ok(self
+            .http
+            .request(method, format!("{}/{}", self.base_url, path))
+            .header("x-api-key", api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION))
+    }
+
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        action: &str,
+    ) -> AppResult<reqwest::Response> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| describe_request_error(&e, "Anthropic", &self.base_url, action))?;
+        if !response.status().is_success() {
+            return Err(error_from_response(response, "Anthropic", action).await);
+        }
+        Ok(response)
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        action: &str,
+    ) -> AppResult<T> {
+        let response = self
+            .send(self.request(reqwest::Method::GET, path)?, action)
+            .await?;
+        response.json().await.map_err(|e| {
+            AppError::provider(
+                ErrorKind::Provider,
+                "Anthropic",
+                format!("{}: {}", action, e),
+            )
+        })
+    }
+}
+
+#[async_trait]
+impl Provider for AnthropicProvider {
+    fn kind(&self) -> &str {
+        "Anthropic"
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn is_configured(&self) -> bool {
+        self.api_key.is_some()
+    }
+
+    async fn list_models(&self) -> AppResult<Vec<String>> {
+        let models: ModelsResponse = self
+            .get_json("models?limit=1000", "Failed to list Anthropic models")
+            .await?;
+        let models: Vec<String> = models
+            .data
+            .iter()
+            .filter_map(|model| model["id"].as_str().map(str::to_string))
+            .collect();
+        if models.is_empty() {
+            return Err(AppError::provider(
+                ErrorKind::NotFound,
+                "Anthropic",
+                "No Anthropic models found",
+            ));
+        }
+        Ok(models)
+    }
+
+    async fn list_model_info(&self) -> AppResult<Vec<ModelInfo>> {
+        Ok(self
+            .list_models()
+            .await?
+            .into_iter()
+            .map(|name| ModelInfo {
+                name,
+                capabilities: claude_capabilities(),
+            })
+            .collect())
+    }
+
+    async fn stream_chat(&self, request: ChatRequest) -> AppResult<ChatStream> {
+        let messages = normalize_messages(&request.messages)?;
+        let (system, messages) = anthropic_messages(&messages);
+        if messages.is_empty() {
+            return Err(AppError::invalid_input("No messages to send to Anthropic"));
+        }
+
+        let options = &request.options;
+        let mut max_tokens = options
+            .num_predict
+            .filter(|n| *n > 0)
+            .map(|n| n as u32)
+            .unwrap_or(DEFAULT_MAX_TOKENS);
+        let thinking = options.thinking_budget.filter(|budget| *budget > 0);
+        if let Some(budget) = thinking {
+            // The thinking budget counts towards max_tokens and has to leave room for the answer
+            if max_tokens <= budget {
+                max_tokens = budget + DEFAULT_MAX_TOKENS;
+            }
+        }
+        let body = MessagesRequest {
+            model: &request.model,
+            max_tokens,
+            messages,
+            system,
+            stream: true,
+            tools: request.tools.iter().map(anthropic_tool).collect(),
+            thinking: thinking.map(|budget| json!({ "type": "enabled", "budget_tokens": budget })),
+            // Sampling parameters are rejected while extended thinking is on
+            temperature: options.temperature.filter(|_| thinking.is_none()),
+            top_p: options.top_p.filter(|_| thinking.is_none()),
+            top_k: options.top_k.filter(|_| thinking.is_none()),
+            stop_sequences: options.stop.as_deref().filter(|stop| !stop.is_empty()),
+        };
+        let response = self
+            .send(
+                self.request(reqwest::Method::POST, "messages")?.json(&body),
+                "Failed to start Anthropic stream",
+            )
+            .await?;
+
+        let (tx, rx) = mpsc::channel::<AppResult<ChatEvent>>(100);
+        let mut events = sse_events(response);
+
+        tokio::spawn(async move {
+            let mut tool_uses: HashMap<u64, PendingToolUse> = HashMap::new();
+            let mut usage = Usage::default();
+            loop {
+                // Stop reading as soon as the consumer goes away (e.g. cancelled)
+                let event = tokio::select! {
+                    event = events.next() => event,
+                    _ = tx.closed() => return,
+                };
+                let event = match event {
+                    Some(Ok(event)) => event,
+                    Some(Err(e)) => {
+                        let _ = tx.send(Err(e.with_provider("Anthropic"))).await;
+                        return;
+                    }
+                    None => {
+                        // A complete reply ends with message_stop; anything else was cut off
+                        let error = AppError::provider(
+                            ErrorKind::Network,
+                            "Anthropic",
+                            "Connection closed before the reply was complete",
+                        );
+                        let _ = tx.send(Err(error)).await;
+                        return;
+                    }
+                };
+                let data: Value = match serde_json::from_str(&event.data) {
+                    Ok(data) => data,
+                    Err(_) => continue,
+                };
+
+                let index = data["index"].as_u64().unwrap_or_default();
+                let out = match data["type"].as_str().unwrap_or_default() {
+                    "message_start" => {
+                        let start = &data["message"]["usage"];
+                        // Cached prompt tokens are reported separately from input_tokens
+                        usage.input_tokens = [
+                            "input_tokens",
+                            "cache_creation_input_tokens",
+                            "cache_read_input_tokens",
+                        ]
+                        .iter()
+                        .filter_map(|key| start[key].as_u64())
+                        .sum();
+                        usage.output_tokens = start["output_tokens"].as_u64().unwrap_or_default();
+                        None
+                    }
+                    "content_block_start" => {
+                        let block = &data["content_block"];
+                        if block["type"] == "tool_use" {
+                            tool_uses.insert(
+                                index,
+                                PendingToolUse {
+                                    id: block["id"].as_str().unwrap_or_default().to_string(),
+                                    name: block["name"].as_str().unwrap_or_default().to_string(),
+                                    input: String::new(),
+                                },
+                            );
+                        }
+                        None
+                    }
+                    "content_block_delta" => {
+                        let delta = &data["delta"];
+                        match delta["type"].as_str().unwrap_or_default() {
+                            "text_delta" => delta["text"]
+                                .as_str()
+                                .map(|text| ChatEvent::Text(text.to_string())),
+                            "thinking_delta" => delta["thinking"]
+                                .as_str()
+                                .map(|text| ChatEvent::Reasoning(text.to_string())),
+                            "signature_delta" => delta["signature"].as_str().map(|signature| {
+                                ChatEvent::ReasoningSignature(signature.to_string())
+                            }),
+                            "input_json_delta" => {
+                                if let (Some(tool_use), Some(json)) =
+                                    (tool_uses.get_mut(&index), delta["partial_json"].as_str())
+                                {
+                                    tool_use.input.push_str(json);
+                                }
+                                None
+                            }
+                            _ => None,
+                        }
+                    }
+                    "content_block_stop" => tool_uses.remove(&index).map(|tool_use| {
+                        ChatEvent::ToolCall(ToolCall {
+                            id: tool_use.id,
+                            name: tool_use.name,
+                            arguments: parse_tool_arguments(&tool_use.input),
+                        })
+                    }),
+                    "message_delta" => {
+                        if let Some(output_tokens) = data["usage"]["output_tokens"].as_u64() {
+                            usage.output_tokens = output_tokens;
+                        }
+                        None
+                    }
+                    "message_stop" => break,
+                    "error" => {
+                        let message = data["error"]["message"].as_str().unwrap_or("unknown error");
+                        let kind = match data["error"]["type"].as_str() {
+                            Some("rate_limit_error") => ErrorKind::RateLimited,
+                            Some("authentication_error" | "permission_error") => ErrorKind::Auth,
+                            _ => ErrorKind::Provider,
+                        };
+                        let error = AppError::provider(kind, "Anthropic", message).with_retryable(
+                            matches!(kind, ErrorKind::RateLimited)
+                                || data["error"]["type"] == "overloaded_error",
+                        );
+                        let _ = tx.send(Err(error)).await;
+                        return;
+                    }
+                    _ => None, // ping
+                };
+
+                if let Some(out) = out {
+                    if tx.send(Ok(out)).await.is_err() {
+                        return; // Receiver was dropped
+                    }
+                }
+            }
+
+            let _ = tx.send(Ok(ChatEvent::Usage(usage))).await;
+        });
+
+        Ok(Box::pin(ReceiverStream::new(rx)))
+    }
+
+    async fn model_info(&self, model: &str) -> AppResult<ModelInfo> {
+        let info: Value = self
+            .get_json(&format!("models/{}", model), "Failed to get model info")
+            .await?;
+        Ok(ModelInfo {
+            name: info["id"].as_str().unwrap_or(model).to_string(),
+            capabilities: claude_capabilities(),
+        })
+    }
+
+    fn health_request(&self) -> AppResult<reqwest::RequestBuilder> {
+        self.request(reqwest::Method::GET, "models?limit=1")
+    }
+}
+
+/// The API reports no limits or prices; those come from the bundled capability table.
+fn claude_capabilities() -> ModelCapabilities {
+    // Every current Claude model accepts images and tools
+    ModelCapabilities {
+        vision: Some(true),
+        tools: Some(true),
+        ..Default::default()
+    }
+}
+
+fn anthropic_tool(tool: &ToolDefinition) -> Value {
+    json!({
+        "name": tool.name,
+        "description": tool.description,
+        "input_schema": tool.parameters,
+    })
+}
+
+/// Split off the system prompt and convert the rest to Messages API turns.
+///
+/// Tool results become `tool_result` blocks in a user turn, and consecutive turns of
+/// the same role are merged since the API expects them to alternate.
+fn anthropic_messages(messages: &[Message]) -> (String, Vec<Value>) {
+    let mut system = Vec::new();
+    let mut out: Vec<Value> = Vec::new();
+
+    for message in messages {
+        let role = match message.role {
+            Role::System => {
+                system.push(message.text());
+                continue;
+            }
+            Role::Assistant => "assistant",
+            Role::User | Role::Tool => "user",
+        };
+
+        let blocks: Vec<Value> = message
+            .content
+            .iter()
+            .filter_map(|content| match content {
+                Content::Text(text) => Some(json!({ "type": "text", "text": text })),
+                // Thinking can only be replayed with its signature
+                Content::Reasoning {
+                    text,
+                    signature: Some(signature),
+                } if message.role == Role::Assistant => Some(json!({
+                    "type": "thinking",
+                    "thinking": text,
+                    "signature": signature,
+                })),
+                Content::Image(ImageData::Base64 { media_type, data }) => Some(json!({
+                    "type": "image",
+                    "source": { "type": "base64", "media_type": media_type, "data": data }
+                })),
+                Content::Image(ImageData::Url(url)) => Some(json!({
+                    "type": "image",
+                    "source": { "type": "url", "url": url }
+                })),
+                Content::ToolCall(call) => Some(json!({
+                    "type": "tool_use",
+                    "id": call.id,
+                    "name": call.name,
+                    "input": call.arguments,
+                })),
+                Content::ToolResult {
+                    call_id,
+                    output,
+                    is_error,
+                    ..
+                } => Some(json!({
+                    "type": "tool_result",
+                    "tool_use_id": call_id,
+                    "content": output,
+                    "is_error": is_error,
+                })),
+                _ => None,
+            })
+            .collect();
+        if blocks.is_empty() {
+            continue;
+        }
+
+        match out.last_mut() {
+            Some(last) if last["role"] == role => {
+                if let Some(content) = last["content"].as_array_mut() {
+                    content.extend(blocks);
+                }
+            }
+            _ => out.push(json!({ "role": role, "content": blocks })),
+        }
+    }
+
+    (system.join("\n\n"), out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::providers::test_server::{Reply, TestServer};
+
+    async fn stream(server: &TestServer, request: ChatRequest) -> Vec<AppResult<ChatEvent>> {
+        let provider = AnthropicProvider::new(Some(&server.url), Some("sk-ant-test")).unwrap();
+        provider.stream_chat(request).await.unwrap().collect().await
+    }
+
+    fn chat_request() -> ChatRequest {
+        ChatRequest {
+            model: "claude-test".to_string(),
+            messages: vec![
+                json!({ "role": "system", "content": "Be brief." }),
+                json!({ "role": "user", "content": "Hi" }),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn streams_thinking_with_its_signature() {
+        let server = TestServer::start(vec![Reply::sse(&[
+            r#"{"type":"message_start","message":{"usage":{"input_tokens":10,"output_tokens":1}}}"#,
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Greet back."}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"signature_delta","signature":"sig-1"}}"#,
+            r#"{"type":"content_block_stop","index":0}"#,
+            r#"{"type":"content_block_start","index":1,"content_block":{"type":"text","text":""}}"#,
+            r#"{"type":"ping"}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"text_delta","text":"Hello"}}"#,
+            r#"{"type":"content_block_stop","index":1}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":7}}"#,
+            r#"{"type":"message_stop"}"#,
+        ])]);
+        let mut request = chat_request();
+        request.options.thinking_budget = Some(1024);
+        let events = stream(&server, request).await;
+
+        let request = server.received();
+        assert_eq!(request.path, "/v1/messages");
+        assert_eq!(request.header("x-api-key"), Some("sk-ant-test"));
+        assert_eq!(request.body["system"], "Be brief.");
+        assert_eq!(request.body["messages"][0]["role"], "user");
+        assert_eq!(
+            request.body["thinking"],
+            json!({ "type": "enabled", "budget_tokens": 1024 })
+        );
+
+        let events: Vec<ChatEvent> = events.into_iter().map(Result::unwrap).collect();
+        assert!(matches!(&events[0], ChatEvent::Reasoning(text) if text == "Greet back."));
+        assert!(matches!(&events[1], ChatEvent::ReasoningSignature(sig) if sig == "sig-1"));
+        assert!(matches!(&events[2], ChatEvent::Text(text) if text == "Hello"));
+        assert!(matches!(
+            events[3],
+            ChatEvent::Usage(Usage {
+                input_tokens: 10,
+                output_tokens: 7
+            })
+        ));
+        assert_eq!(events.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn assembles_tool_input_from_json_deltas() {
+        let server = TestServer::start(vec![Reply::sse(&[
+            r#"{"type":"message_start","message":{"usage":{"input_tokens":5,"output_tokens":1}}}"#,
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"tool_use","id":"toolu_1","name":"get_weather","input":{}}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"input_json_delta","partial_json":"{\"city\":"}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"input_json_delta","partial_json":" \"Oslo\"}"}}"#,
+            r#"{"type":"content_block_stop","index":0}"#,
+            r#"{"type":"message_stop"}"#,
+        ])]);
+        let events = stream(&server, chat_request()).await;
+
+        assert_eq!(events.len(), 2);
+        match events[0].as_ref().unwrap() {
+            ChatEvent::ToolCall(call) => {
+                assert_eq!(call.id, "toolu_1");
+                assert_eq!(call.name, "get_weather");
+                assert_eq!(call.arguments, json!({ "city": "Oslo" }));
+            }
+            event => panic!("expected a tool call, got {:?}", event),
+        }
+    }
+
+    #[tokio::test]
+    async fn fails_a_stream_without_message_stop() {
+        let server = TestServer::start(vec![Reply::sse(&[
+            r#"{"type":"message_start","message":{"usage":{"input_tokens":5,"output_tokens":1}}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hel"}}"#,
+        ])]);
+        let events = stream(&server, chat_request()).await;
+
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], Ok(ChatEvent::Text(_))));
+        let error = events[1].as_ref().unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Network);
+        assert_eq!(error.provider.as_deref(), Some("Anthropic"));
+    }
+
+    #[tokio::test]
+    async fn counts_cached_prompt_tokens_as_input() {
+        let server = TestServer::start(vec![Reply::sse(&[
+            r#"{"type":"message_start","message":{"usage":{"input_tokens":4,"cache_creation_input_tokens":100,"cache_read_input_tokens":2000,"output_tokens":1}}}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":3}}"#,
+            r#"{"type":"message_stop"}"#,
+        ])]);
+        let events = stream(&server, chat_request()).await;
+
+        assert!(matches!(
+            events.last(),
+            Some(Ok(ChatEvent::Usage(Usage {
+                input_tokens: 2104,
+                output_tokens: 3
+            })))
+        ));
+    }
+}
@@ -0,0 +1,203 @@
+use super::types::ModelInfo;
+use crate::modules::error::{AppError, AppResult, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How long a discovered model list is used before the provider is asked again.
+pub const MODEL_CACHE_TTL_SECS: u64 = 6 * 60 * 60;
+
+/// Model lists last discovered per provider, kept on disk so an offline provider can
+/// still offer the models it had.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelCache {
+    #[serde(default)]
+    entries: HashMap<String, CachedModels>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedModels {
+    pub base_url: String, // Lists from a different endpoint are not reused
+    pub fetched_at: u64,  // Unix seconds
+    pub models: Vec<ModelInfo>,
+}
+
+impl CachedModels {
+    pub fn is_fresh(&self) -> bool {
+        now().saturating_sub(self.fetched_at) < MODEL_CACHE_TTL_SECS
+    }
+}
+
+/// What to list for a provider after asking it for its models.
+#[derive(Debug)]
+pub struct Listing {
+    pub models: Vec<ModelInfo>,
+    pub error: Option<AppError>, // The request failed and `models` is the last known list
+}
+
+impl ModelCache {
+    /// Read the cache; a missing or unreadable file is an empty cache.
+    pub fn load(path: &Path) -> Self {
+        std::fs::read(path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> AppResult<()> {
+        let json = serde_json::to_vec_pretty(self).context("serialize model cache failed")?;
+        std::fs::write(path, json).context("write model cache failed")
+    }
+
+    /// The last list discovered for `kind` at `base_url`, however old.
+    pub fn get(&self, kind: &str, base_url: &str) -> Option<&CachedModels> {
+        self.entries
+            .get(kind)
+            .filter(|cached| cached.base_url == base_url)
+    }
+
+    /// Whether the list for `kind` at `base_url` can be used without asking again.
+    pub fn is_fresh(&self, kind: &str, base_url: &str) -> bool {
+        self.get(kind, base_url).is_some_and(CachedModels::is_fresh)
+    }
+
+    /// Mark the list for `kind` as stale, so it is fetched again but still served if that
+    /// fails.
+    pub fn invalidate(&mut self, kind: &str) {
+        if let Some(cached) = self.entries.get_mut(kind) {
+            cached.fetched_at = 0;
+        }
+    }
+
+    pub fn invalidate_all(&mut self) {
+        for cached in self.entries.values_mut() {
+            cached.fetched_at = 0;
+        }
+    }
+
+    /// Record the outcome of asking a provider for its models. A failure keeps the last
+    /// list known for the same endpoint, however old; a provider that was never reached
+    /// has none.
+    pub fn record(
+        &mut self,
+        kind: &str,
+        base_url: &str,
+        fetched: AppResult<Vec<ModelInfo>>,
+    ) -> Listing {
+        match fetched {
+            Ok(models) => {
+                self.insert(kind, base_url, models.clone());
+                Listing {
+                    models,
+                    error: None,
+                }
+            }
+            Err(error) => Listing {
+                models: self
+                    .get(kind, base_url)
+                    .map(|cached| cached.models.clone())
+                    .unwrap_or_default(),
+                error: Some(error),
+            },
+        }
+    }
+
+    pub fn insert(&mut self, kind: &str, base_url: &str, models: Vec<ModelInfo>) {
+        self.entries.insert(
+            kind.to_string(),
+            CachedModels {
+                base_url: base_url.to_string(),
+                fetched_at: now(),
+                models,
+            },
+        );
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn models(names: &[&str]) -> Vec<ModelInfo> {
+        names.iter().map(|name| ModelInfo::named(*name)).collect()
+    }
+
+    fn names(models: &[ModelInfo]) -> Vec<&str> {
+        models.iter().map(|info| info.name.as_str()).collect()
+    }
+
+    #[test]
+    fn expires_lists_after_the_ttl() {
+        let mut cache = ModelCache::default();
+        cache.insert("OpenAI", "https://api.openai.com/v1", models(&["gpt-4.1"]));
+        assert!(cache.is_fresh("OpenAI", "https://api.openai.com/v1"));
+
+        cache.entries.get_mut("OpenAI").unwrap().fetched_at = now() - MODEL_CACHE_TTL_SECS;
+        assert!(!cache.is_fresh("OpenAI", "https://api.openai.com/v1"));
+        // Expired lists are still kept for when the provider cannot be reached
+        assert!(cache.get("OpenAI", "https://api.openai.com/v1").is_some());
+    }
+
+    #[test]
+    fn ignores_lists_from_another_endpoint() {
+        let mut cache = ModelCache::default();
+        cache.insert("OpenAI", "https://api.openai.com/v1", models(&["gpt-4.1"]));
+
+        assert!(cache.get("OpenAI", "http://localhost:8000/v1").is_none());
+        assert!(!cache.is_fresh("OpenAI", "http://localhost:8000/v1"));
+    }
+
+    #[test]
+    fn serves_the_stale_list_when_fetching_fails() {
+        let mut cache = ModelCache::default();
+        cache.insert("Groq", "https://api.groq.com/openai/v1", models(&["llama"]));
+        cache.invalidate_all();
+
+        let listing = cache.record(
+            "Groq",
+            "https://api.groq.com/openai/v1",
+            Err(AppError::internal("offline")),
+        );
+        assert_eq!(names(&listing.models), ["llama"]);
+        assert_eq!(listing.error.unwrap().message, "offline");
+        assert!(!cache.is_fresh("Groq", "https://api.groq.com/openai/v1"));
+
+        let listing = cache.record("Groq", "https://api.groq.com/openai/v1", Ok(models(&["qwen"])));
+        assert_eq!(names(&listing.models), ["qwen"]);
+        assert!(listing.error.is_none());
+        assert!(cache.is_fresh("Groq", "https://api.groq.com/openai/v1"));
+    }
+
+    #[test]
+    fn lists_nothing_for_a_provider_never_reached() {
+        let mut cache = ModelCache::default();
+        let listing = cache.record("Mistral", "https://api.mistral.ai/v1", Err(AppError::internal("offline")));
+
+        assert!(listing.models.is_empty());
+        assert!(listing.error.is_some());
+        assert!(cache.get("Mistral", "https://api.mistral.ai/v1").is_none());
+    }
+
+    #[test]
+    fn invalidates_one_provider() {
+        let mut cache = ModelCache::default();
+        cache.insert("OpenAI", "https://api.openai.com/v1", models(&["gpt-4.1"]));
+        cache.insert("Ollama", "http://localhost:11434/", models(&["llama3.2"]));
+
+        cache.invalidate("Ollama");
+        assert!(cache.is_fresh("OpenAI", "https://api.openai.com/v1"));
+        assert!(!cache.is_fresh("Ollama", "http://localhost:11434/"));
+        assert_eq!(
+            names(&cache.get("Ollama", "http://localhost:11434/").unwrap().models),
+            ["llama3.2"]
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use super::types::ModelCapabilities;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Limits, features and prices of popular models, keyed by model id prefix. Used where
+/// providers do not report them (OpenAI, Anthropic, xAI, ...).
+const BUNDLED_CAPABILITIES: &str = include_str!("model_capabilities.json");
+
+fn bundled_table() -> &'static HashMap<String, ModelCapabilities> {
+    static TABLE: OnceLock<HashMap<String, ModelCapabilities>> = OnceLock::new();
+    // The tests below make sure the bundled file parses
+    TABLE.get_or_init(|| {
+        serde_json::from_str(BUNDLED_CAPABILITIES).expect("model_capabilities.json is invalid")
+    })
+}
+
+/// The entry whose key is the longest prefix of `model`, with or without a vendor
+/// prefix such as OpenRouter's `openai/`.
+fn lookup<'a>(
+    table: &'a HashMap<String, ModelCapabilities>,
+    model: &str,
+) -> Option<&'a ModelCapabilities> {
+    let id = model.rsplit('/').next().unwrap_or(model);
+    table
+        .iter()
+        .filter(|(key, _)| model.starts_with(key.as_str()) || id.starts_with(key.as_str()))
+        .max_by_key(|(key, _)| key.len())
+        .map(|(_, capabilities)| capabilities)
+}
+
+/// Capabilities of `model`: the bundled table, overlaid with what the provider reported,
+/// overlaid with the user's overrides from settings.
+pub fn resolve_capabilities(
+    model: &str,
+    reported: &ModelCapabilities,
+    overrides: Option<&HashMap<String, ModelCapabilities>>,
+) -> ModelCapabilities {
+    let capabilities = lookup(bundled_table(), model)
+        .cloned()
+        .unwrap_or_default()
+        .merged_with(reported);
+    match overrides.and_then(|overrides| lookup(overrides, model)) {
+        Some(overrides) => capabilities.merged_with(overrides),
+        None => capabilities,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_table_parses() {
+        let table: HashMap<String, ModelCapabilities> =
+            serde_json::from_str(BUNDLED_CAPABILITIES).expect("model_capabilities.json is invalid");
+        assert!(!table.is_empty());
+        for (model, capabilities) in &table {
+            assert!(
+                capabilities.context_length.is_some(),
+                "{} has no context_length",
+                model
+            );
+        }
+    }
+
+    #[test]
+    fn longest_prefix_wins() {
+        let table = bundled_table();
+        let mini = lookup(table, "gpt-4o-mini-2024-07-18").unwrap();
+        assert_eq!(mini, &table["gpt-4o-mini"]);
+        let routed = lookup(table, "openai/gpt-4o-2024-08-06").unwrap();
+        assert_eq!(routed, &table["gpt-4o"]);
+        assert!(lookup(table, "unknown-model").is_none());
+    }
+
+    #[test]
+    fn overrides_win_over_reported_and_bundled() {
+        let reported = ModelCapabilities {
+            context_length: Some(1000),
+            ..Default::default()
+        };
+        let overrides = HashMap::from([(
+            "gpt-4o".to_string(),
+            ModelCapabilities {
+                vision: Some(false),
+                ..Default::default()
+            },
+        )]);
+        let capabilities = resolve_capabilities("gpt-4o", &reported, Some(&overrides));
+        assert_eq!(capabilities.context_length, Some(1000));
+        assert_eq!(capabilities.vision, Some(false));
+        assert_eq!(capabilities.tools, Some(true));
+    }
+}
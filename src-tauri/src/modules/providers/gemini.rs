@@ -0,0 +1,682 @@
+use super::http::{describe_request_error, error_from_response};
+use super::messages::{normalize_messages, sniff_image_type, Content, ImageData, Message, Role};
+use super::provider::Provider;
+use super::sse::sse_events;
+use super::types::{
+    ChatEvent, ChatRequest, ChatStream, ModelCapabilities, ModelInfo, ToolCall, ToolDefinition,
+    Usage,
+};
+use crate::modules::error::{AppError, AppResult, ErrorKind};
+use async_trait::async_trait;
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+pub const DEFAULT_GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+// Inline data counts towards the 20 MB request limit
+const MAX_INLINE_IMAGE_BYTES: usize = 15 * 1024 * 1024;
+// Reasons a candidate was stopped without a usable reply
+const BLOCKED_FINISH_REASONS: &[&str] = &[
+    "SAFETY",
+    "RECITATION",
+    "BLOCKLIST",
+    "PROHIBITED_CONTENT",
+    "SPII",
+    "IMAGE_SAFETY",
+];
+
+/// A client for the Gemini `generateContent` API.
+#[derive(Clone)]
+pub struct GeminiProvider {
+    base_url: String,
+    api_key: Option<String>,
+    http: reqwest::Client,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateContentRequest {
+    contents: Vec<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_instruction: Option<Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<Value>,
+    generation_config: GenerationConfig,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerationConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_k: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_output_tokens: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking_config: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelsResponse {
+    #[serde(default)]
+    models: Vec<Value>,
+}
+
+impl GeminiProvider {
+    pub fn new(base_url: Option<&str>, api_key: Option<&str>) -> AppResult<Self> {
+        let base_url = base_url
+            .unwrap_or(DEFAULT_GEMINI_BASE_URL)
+            .trim()
+            .trim_end_matches('/');
+        reqwest::Url::parse(base_url).map_err(|e| {
+            AppError::invalid_input(format!("Invalid Gemini URL '{}': {}", base_url, e))
+                .with_provider("Gemini")
+        })?;
+        Ok(Self {
+            base_url: base_url.to_string(),
+            api_key: api_key
+                .filter(|api_key| !api_key.trim().is_empty())
+                .map(str::to_string),
+            http: reqwest::Client::new(),
+        })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> AppResult<reqwest::RequestBuilder> {
+        let api_key = self.api_key.as_deref().ok_or_else(|| {
+            AppError::provider(
+                ErrorKind::NotConfigured,
+                "Gemini",
+                "Gemini API key not found. Please configure it in settings.",
+            )
+        })?;
+        Ok(self
+            .http
+            .request(method, format!("{}/{}", self.base_url, path))
+            .header("x-goog-api-key", api_key))
+    }
+
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        action: &str,
+    ) -> AppResult<reqwest::Response> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| describe_request_error(&e, "Gemini", &self.base_url, action))?;
+        if !response.status().is_success() {
+            return Err(error_from_response(response, "Gemini", action).await);
+        }
+        Ok(response)
+    }
+
+    /// Gemini only reads `fileData` from its own file store, so remote images are
+    /// downloaded and sent inline with the media type of their content.
+    async fn inline_remote_images(&self, messages: &mut [Message]) -> AppResult<()> {
+        for content in messages.iter_mut().flat_map(|m| m.content.iter_mut()) {
+            if let Content::Image(ImageData::Url(url)) = content {
+                let image = self.fetch_image(url).await?;
+                *content = Content::Image(image);
+            }
+        }
+        Ok(())
+    }
+
+    async fn fetch_image(&self, url: &str) -> AppResult<ImageData> {
+        let failed = |reason: String| {
+            AppError::provider(
+                ErrorKind::InvalidInput,
+                "Gemini",
+                format!("Could not attach image {}: {}", url, reason),
+            )
+        };
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| failed(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(failed(format!("the server returned {}", response.status())));
+        }
+        let bytes = response.bytes().await.map_err(|e| failed(e.to_string()))?;
+        if bytes.len() > MAX_INLINE_IMAGE_BYTES {
+            return Err(failed(format!(
+                "it is larger than {} MB",
+                MAX_INLINE_IMAGE_BYTES / (1024 * 1024)
+            )));
+        }
+        let media_type = sniff_image_type(&bytes)
+            .ok_or_else(|| failed("it is not a PNG, JPEG, GIF or WebP image".to_string()))?;
+        Ok(ImageData::Base64 {
+            media_type: media_type.to_string(),
+            data: BASE64_STANDARD.encode(&bytes),
+        })
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        action: &str,
+    ) -> AppResult<T> {
+        let response = self
+            .send(self.request(reqwest::Method::GET, path)?, action)
+            .await?;
+        response.json().await.map_err(|e| {
+            AppError::provider(ErrorKind::Provider, "Gemini", format!("{}: {}", action, e))
+        })
+    }
+}
+
+#[async_trait]
+impl Provider for GeminiProvider {
+    fn kind(&self) -> &str {
+        "Gemini"
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn is_configured(&self) -> bool {
+        self.api_key.is_some()
+    }
+
+    async fn list_models(&self) -> AppResult<Vec<String>> {
+        Ok(self
+            .list_model_info()
+            .await?
+            .into_iter()
+            .map(|info| info.name)
+            .collect())
+    }
+
+    async fn list_model_info(&self) -> AppResult<Vec<ModelInfo>> {
+        let models: ModelsResponse = self
+            .get_json("models?pageSize=1000", "Failed to list Gemini models")
+            .await?;
+        let models: Vec<ModelInfo> = models
+            .models
+            .iter()
+            .filter(|model| supports_generate_content(model))
+            .filter_map(|model| {
+                let name = model["name"].as_str()?.trim_start_matches("models/");
+                Some(gemini_model_info(name, model))
+            })
+            .collect();
+        if models.is_empty() {
+            return Err(AppError::provider(
+                ErrorKind::NotFound,
+                "Gemini",
+                "No Gemini models found",
+            ));
+        }
+        Ok(models)
+    }
+
+    async fn stream_chat(&self, request: ChatRequest) -> AppResult<ChatStream> {
+        let mut messages = normalize_messages(&request.messages)?;
+        self.inline_remote_images(&mut messages).await?;
+        let (system, contents) = gemini_contents(&messages);
+        if contents.is_empty() {
+            return Err(AppError::invalid_input("No messages to send to Gemini"));
+        }
+
+        let options = &request.options;
+        let body = GenerateContentRequest {
+            contents,
+            system_instruction: (!system.is_empty())
+                .then(|| json!({ "parts": [{ "text": system }] })),
+            tools: gemini_tools(&request.tools),
+            generation_config: GenerationConfig {
+                temperature: options.temperature,
+                top_p: options.top_p,
+                top_k: options.top_k,
+                max_output_tokens: options.num_predict.filter(|n| *n > 0),
+                seed: options.seed,
+                stop_sequences: options.stop.clone().filter(|stop| !stop.is_empty()),
+                thinking_config: options
+                    .thinking_budget
+                    .map(|budget| json!({ "thinkingBudget": budget, "includeThoughts": true })),
+            },
+        };
+        let path = format!(
+            "models/{}:streamGenerateContent?alt=sse",
+            request.model.trim_start_matches("models/")
+        );
+        let response = self
+            .send(
+                self.request(reqwest::Method::POST, &path)?.json(&body),
+                "Failed to start Gemini stream",
+            )
+            .await?;
+
+        let (tx, rx) = mpsc::channel::<AppResult<ChatEvent>>(100);
+        let mut events = sse_events(response);
+
+        tokio::spawn(async move {
+            let mut usage = None;
+            let mut finished = false;
+            loop {
+                // Stop reading as soon as the consumer goes away (e.g. cancelled)
+                let event = tokio::select! {
+                    event = events.next() => event,
+                    _ = tx.closed() => return,
+                };
+                let event = match event {
+                    Some(Ok(event)) => event,
+                    Some(Err(e)) => {
+                        let _ = tx.send(Err(e.with_provider("Gemini"))).await;
+                        return;
+                    }
+                    None if finished => break,
+                    None => {
+                        // The last chunk of a complete reply carries a finishReason
+                        let error = AppError::provider(
+                            ErrorKind::Network,
+                            "Gemini",
+                            "Connection closed before the reply was complete",
+                        );
+                        let _ = tx.send(Err(error)).await;
+                        return;
+                    }
+                };
+                let chunk: Value = match serde_json::from_str(&event.data) {
+                    Ok(chunk) => chunk,
+                    Err(_) => continue,
+                };
+                if let Some(message) = chunk["error"]["message"].as_str() {
+                    let _ = tx
+                        .send(Err(AppError::provider(
+                            ErrorKind::Provider,
+                            "Gemini",
+                            message,
+                        )))
+                        .await;
+                    return;
+                }
+                if let Some(reason) = chunk["promptFeedback"]["blockReason"].as_str() {
+                    let error = AppError::provider(
+                        ErrorKind::InvalidInput,
+                        "Gemini",
+                        format!("Prompt blocked by Gemini ({})", reason),
+                    );
+                    let _ = tx.send(Err(error)).await;
+                    return;
+                }
+
+                // Usage is cumulative; the last chunk carries the totals
+                let metadata = &chunk["usageMetadata"];
+                if metadata.is_object() {
+                    usage = Some(Usage {
+                        input_tokens: metadata["promptTokenCount"].as_u64().unwrap_or_default(),
+                        output_tokens: ["candidatesTokenCount", "thoughtsTokenCount"]
+                            .iter()
+                            .filter_map(|key| metadata[key].as_u64())
+                            .sum(),
+                    });
+                }
+
+                let candidate = &chunk["candidates"][0];
+                let parts = candidate["content"]["parts"].as_array();
+                for part in parts.into_iter().flatten() {
+                    let out = if let Some(call) = part.get("functionCall") {
+                        // Gemini has no call ids, so generate one for results to refer to
+                        Some(ChatEvent::ToolCall(ToolCall {
+                            id: call["id"].as_str().map(str::to_string).unwrap_or_else(|| {
+                                format!("call_{}", uuid::Uuid::new_v4().simple())
+                            }),
+                            name: call["name"].as_str().unwrap_or_default().to_string(),
+                            arguments: match &call["args"] {
+                                Value::Null => json!({}),
+                                args => args.clone(),
+                            },
+                        }))
+                    } else {
+                        part["text"]
+                            .as_str()
+                            .filter(|text| !text.is_empty())
+                            .map(|text| match part["thought"].as_bool() {
+                                Some(true) => ChatEvent::Reasoning(text.to_string()),
+                                _ => ChatEvent::Text(text.to_string()),
+                            })
+                    };
+                    if let Some(out) = out {
+                        if tx.send(Ok(out)).await.is_err() {
+                            return; // Receiver was dropped
+                        }
+                    }
+                }
+
+                if let Some(reason) = candidate["finishReason"].as_str() {
+                    if BLOCKED_FINISH_REASONS.contains(&reason) {
+                        let error = AppError::provider(
+                            ErrorKind::Provider,
+                            "Gemini",
+                            format!("Gemini stopped the reply ({})", reason),
+                        );
+                        let _ = tx.send(Err(error)).await;
+                        return;
+                    }
+                    finished = true;
+                }
+            }
+
+            if let Some(usage) = usage {
+                let _ = tx.send(Ok(ChatEvent::Usage(usage))).await;
+            }
+        });
+
+        Ok(Box::pin(ReceiverStream::new(rx)))
+    }
+
+    async fn model_info(&self, model: &str) -> AppResult<ModelInfo> {
+        let name = model.trim_start_matches("models/");
+        let info: Value = self
+            .get_json(&format!("models/{}", name), "Failed to get model info")
+            .await?;
+        Ok(gemini_model_info(name, &info))
+    }
+
+    fn health_request(&self) -> AppResult<reqwest::RequestBuilder> {
+        self.request(reqwest::Method::GET, "models?pageSize=1")
+    }
+}
+
+fn gemini_model_info(name: &str, info: &Value) -> ModelInfo {
+    // Gemini models take images, function calls and JSON output; Gemma and embedding
+    // models do not
+    let is_gemini = name.starts_with("gemini") && supports_generate_content(info);
+    ModelInfo {
+        name: name.to_string(),
+        capabilities: ModelCapabilities {
+            context_length: info["inputTokenLimit"].as_u64(),
+            max_output_tokens: info["outputTokenLimit"].as_u64(),
+            vision: Some(is_gemini),
+            tools: Some(is_gemini),
+            json_mode: Some(is_gemini),
+            reasoning: info["thinking"].as_bool(),
+            ..Default::default()
+        },
+    }
+}
+
+fn supports_generate_content(model: &Value) -> bool {
+    model["supportedGenerationMethods"]
+        .as_array()
+        .is_some_and(|methods| methods.iter().any(|m| m == "generateContent"))
+}
+
+fn gemini_tools(tools: &[ToolDefinition]) -> Vec<Value> {
+    if tools.is_empty() {
+        return Vec::new();
+    }
+    // parametersJsonSchema takes plain JSON Schema, unlike the OpenAPI subset of `parameters`
+    let declarations: Vec<Value> = tools
+        .iter()
+        .map(|tool| {
+            json!({
+                "name": tool.name,
+                "description": tool.description,
+                "parametersJsonSchema": tool.parameters,
+            })
+        })
+        .collect();
+    vec![json!({ "functionDeclarations": declarations })]
+}
+
+/// Split off the system instruction and convert the rest to Gemini contents.
+///
+/// Assistant turns use the `model` role, tool results become `functionResponse` parts
+/// in a user turn, and consecutive turns of the same role are merged. Remote images must
+/// have been inlined first.
+fn gemini_contents(messages: &[Message]) -> (String, Vec<Value>) {
+    let mut system = Vec::new();
+    let mut out: Vec<Value> = Vec::new();
+
+    for message in messages {
+        let role = match message.role {
+            Role::System => {
+                system.push(message.text());
+                continue;
+            }
+            Role::Assistant => "model",
+            Role::User | Role::Tool => "user",
+        };
+
+        let parts: Vec<Value> = message
+            .content
+            .iter()
+            .filter_map(|content| match content {
+                Content::Text(text) => Some(json!({ "text": text })),
+                Content::Image(ImageData::Base64 { media_type, data }) => Some(json!({
+                    "inlineData": { "mimeType": media_type, "data": data }
+                })),
+                Content::ToolCall(call) => Some(json!({
+                    "functionCall": { "name": call.name, "args": call.arguments }
+                })),
+                Content::ToolResult { name, output, .. } => Some(json!({
+                    "functionResponse": {
+                        "name": name,
+                        "response": { "content": output }
+                    }
+                })),
+                _ => None,
+            })
+            .collect();
+        if parts.is_empty() {
+            continue;
+        }
+
+        match out.last_mut() {
+            Some(last) if last["role"] == role => {
+                if let Some(existing) = last["parts"].as_array_mut() {
+                    existing.extend(parts);
+                }
+            }
+            _ => out.push(json!({ "role": role, "parts": parts })),
+        }
+    }
+
+    (system.join("\n\n"), out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::providers::test_server::{Reply, TestServer};
+
+    fn message(role: Role, content: Vec<Content>) -> Message {
+        Message { role, content }
+    }
+
+    fn text(text: &str) -> Content {
+        Content::Text(text.to_string())
+    }
+
+    #[test]
+    fn splits_off_the_system_instruction() {
+        let (system, contents) = gemini_contents(&[
+            message(Role::System, vec![text("Be brief.")]),
+            message(Role::User, vec![text("Hi")]),
+            message(Role::System, vec![text("Answer in English.")]),
+        ]);
+
+        assert_eq!(system, "Be brief.\n\nAnswer in English.");
+        assert_eq!(
+            contents,
+            [json!({ "role": "user", "parts": [{ "text": "Hi" }] })]
+        );
+    }
+
+    #[test]
+    fn converts_tool_turns_and_merges_roles() {
+        let (_, contents) = gemini_contents(&[
+            message(Role::User, vec![text("Weather in Oslo?")]),
+            message(
+                Role::Assistant,
+                vec![Content::ToolCall(ToolCall {
+                    id: "call_1".to_string(),
+                    name: "get_weather".to_string(),
+                    arguments: json!({ "city": "Oslo" }),
+                })],
+            ),
+            message(
+                Role::Tool,
+                vec![Content::ToolResult {
+                    call_id: "call_1".to_string(),
+                    name: "get_weather".to_string(),
+                    output: "Rain".to_string(),
+                    is_error: false,
+                }],
+            ),
+            message(
+                Role::User,
+                vec![Content::Image(ImageData::Base64 {
+                    media_type: "image/png".to_string(),
+                    data: "iVBORw0KGgo=".to_string(),
+                })],
+            ),
+        ]);
+
+        assert_eq!(
+            contents,
+            [
+                json!({ "role": "user", "parts": [{ "text": "Weather in Oslo?" }] }),
+                json!({ "role": "model", "parts": [
+                    { "functionCall": { "name": "get_weather", "args": { "city": "Oslo" } } }
+                ] }),
+                json!({ "role": "user", "parts": [
+                    { "functionResponse": { "name": "get_weather", "response": { "content": "Rain" } } },
+                    { "inlineData": { "mimeType": "image/png", "data": "iVBORw0KGgo=" } }
+                ] }),
+            ]
+        );
+    }
+
+    async fn stream(server: &TestServer, messages: Vec<Value>) -> Vec<AppResult<ChatEvent>> {
+        let provider = GeminiProvider::new(Some(&server.url), Some("AIza-test")).unwrap();
+        let request = ChatRequest {
+            model: "gemini-test".to_string(),
+            messages,
+            ..Default::default()
+        };
+        provider.stream_chat(request).await.unwrap().collect().await
+    }
+
+    fn hi() -> Vec<Value> {
+        vec![json!({ "role": "user", "content": "Hi" })]
+    }
+
+    #[tokio::test]
+    async fn streams_thoughts_text_and_usage() {
+        let server = TestServer::start(vec![Reply::sse(&[
+            r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"Greet back.","thought":true}]}}]}"#,
+            r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"Hello"}]}}],"usageMetadata":{"promptTokenCount":4,"candidatesTokenCount":1}}"#,
+            r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"!"}]},"finishReason":"STOP"}],"usageMetadata":{"promptTokenCount":4,"candidatesTokenCount":2,"thoughtsTokenCount":3}}"#,
+        ])]);
+        let events = stream(&server, hi()).await;
+
+        let request = server.received();
+        assert_eq!(
+            request.path,
+            "/v1/models/gemini-test:streamGenerateContent?alt=sse"
+        );
+        assert_eq!(request.header("x-goog-api-key"), Some("AIza-test"));
+
+        let events: Vec<ChatEvent> = events.into_iter().map(Result::unwrap).collect();
+        assert!(matches!(&events[0], ChatEvent::Reasoning(text) if text == "Greet back."));
+        assert!(matches!(&events[1], ChatEvent::Text(text) if text == "Hello"));
+        assert!(matches!(&events[2], ChatEvent::Text(text) if text == "!"));
+        assert!(matches!(
+            events[3],
+            ChatEvent::Usage(Usage {
+                input_tokens: 4,
+                output_tokens: 5
+            })
+        ));
+        assert_eq!(events.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn fails_a_blocked_reply() {
+        let server = TestServer::start(vec![Reply::sse(&[
+            r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"Sure"}]}}]}"#,
+            r#"{"candidates":[{"finishReason":"SAFETY"}]}"#,
+        ])]);
+        let events = stream(&server, hi()).await;
+
+        assert_eq!(events.len(), 2);
+        let error = events[1].as_ref().unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Provider);
+        assert!(error.message.contains("SAFETY"));
+    }
+
+    #[tokio::test]
+    async fn fails_a_stream_without_finish_reason() {
+        let server = TestServer::start(vec![Reply::sse(&[
+            r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"Hel"}]}}]}"#,
+        ])]);
+        let events = stream(&server, hi()).await;
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].as_ref().unwrap_err().kind, ErrorKind::Network);
+    }
+
+    #[tokio::test]
+    async fn inlines_remote_images_with_their_media_type() {
+        let server = TestServer::start(vec![
+            Reply::file("application/octet-stream", "GIF89a\x01\x00\x01\x00"),
+            Reply::sse(&[
+                r#"{"candidates":[{"content":{"parts":[{"text":"A dot"}]},"finishReason":"STOP"}]}"#,
+            ]),
+        ]);
+        let image_url = format!("{}/images/dot", server.url);
+        let events = stream(
+            &server,
+            vec![json!({ "role": "user", "parts": [
+                { "type": "text", "text": "What is this?" },
+                { "type": "file", "mediaType": "image/png", "url": image_url }
+            ] })],
+        )
+        .await;
+        assert!(events.iter().all(Result::is_ok));
+
+        assert_eq!(server.received().path, "/v1/images/dot");
+        let request = server.received();
+        assert_eq!(
+            request.body["contents"][0]["parts"][1],
+            json!({ "inlineData": {
+                "mimeType": "image/gif",
+                "data": BASE64_STANDARD.encode("GIF89a\x01\x00\x01\x00")
+            } })
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_remote_files_that_are_not_images() {
+        let server = TestServer::start(vec![Reply::file("text/html", "<html></html>")]);
+        let provider = GeminiProvider::new(Some(&server.url), Some("AIza-test")).unwrap();
+        let request = ChatRequest {
+            model: "gemini-test".to_string(),
+            messages: vec![json!({ "role": "user", "parts": [
+                { "type": "file", "mediaType": "image/png", "url": format!("{}/page", server.url) }
+            ] })],
+            ..Default::default()
+        };
+
+        let error = provider.stream_chat(request).await.err().unwrap();
+        assert_eq!(error.kind, ErrorKind::InvalidInput);
+        assert!(error.message.contains("not a PNG, JPEG, GIF or WebP image"));
+    }
+}
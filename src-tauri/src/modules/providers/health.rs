@@ -0,0 +1,312 @@
+use crate::modules::error::AppResult;
+use serde::Serialize;
+use serde_json::Value;
+use std::error::Error as _;
+use std::io;
+use std::net::ToSocketAddrs;
+use std::time::{Duration, Instant};
+
+/// How long a health check waits before reporting the provider as timed out.
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Reachable,
+    NotConfigured,
+    AuthFailed,
+    RateLimited,
+    DnsError,
+    TlsError,
+    Unreachable,
+    Timeout,
+    ServerError,
+    Error,
+}
+
+/// Outcome of a provider health check, with a message the user can act on.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderHealth {
+    pub adapter_kind: String,
+    pub base_url: String,
+    pub status: HealthStatus,
+    pub latency_ms: Option<u64>, // Round trip of the check request, when it got a response
+    pub server_version: Option<String>, // Only servers that report one, e.g. Ollama
+    pub message: String,
+}
+
+/// Send a provider's health check request and classify the result.
+pub async fn check_health(
+    kind: &str,
+    base_url: &str,
+    is_configured: bool,
+    request: AppResult<reqwest::RequestBuilder>,
+) -> ProviderHealth {
+    let report = |status, latency_ms, server_version, message: String| ProviderHealth {
+        adapter_kind: kind.to_string(),
+        base_url: base_url.to_string(),
+        status,
+        latency_ms,
+        server_version,
+        message,
+    };
+
+    let request = match request {
+        Ok(request) if is_configured => request,
+        Ok(_) => {
+            return report(
+                HealthStatus::NotConfigured,
+                None,
+                None,
+                format!(
+                    "{} API key not found. Please configure it in settings.",
+                    kind
+                ),
+            )
+        }
+        Err(e) => return report(HealthStatus::NotConfigured, None, None, e.message),
+    };
+
+    let started = Instant::now();
+    let response = match request.timeout(HEALTH_TIMEOUT).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            let mut status = classify_request_error(&e);
+            if status == HealthStatus::Unreachable && !resolves(e.url()).await {
+                status = HealthStatus::DnsError;
+            }
+            return report(
+                status,
+                None,
+                None,
+                error_message(status, kind, base_url, &e),
+            );
+        }
+    };
+    let latency_ms = Some(started.elapsed().as_millis() as u64);
+
+    let status_code = response.status();
+    let body = response.text().await.unwrap_or_default();
+    let body = serde_json::from_str::<Value>(&body).unwrap_or(Value::String(body));
+    let status = match status_code.as_u16() {
+        200..=299 => HealthStatus::Reachable,
+        401 | 403 => HealthStatus::AuthFailed,
+        429 => HealthStatus::RateLimited,
+        500..=599 => HealthStatus::ServerError,
+        _ => HealthStatus::Error,
+    };
+    let message = match status {
+        HealthStatus::Reachable => format!("{} is reachable.", kind),
+        HealthStatus::AuthFailed => format!(
+            "{} rejected the API key ({}). Check the key in settings.",
+            kind, status_code
+        ),
+        HealthStatus::RateLimited => format!(
+            "{} is rate limiting requests. Wait a moment or check your plan's limits.",
+            kind
+        ),
+        HealthStatus::ServerError => format!(
+            "{} is having problems ({}). Try again later.",
+            kind, status_code
+        ),
+        _ if status_code == reqwest::StatusCode::NOT_FOUND => format!(
+            "{} answered at {} but the API was not found. Check the base URL in settings.",
+            kind, base_url
+        ),
+        _ => format!(
+            "{} returned {}: {}",
+            kind,
+            status_code,
+            response_error(&body)
+        ),
+    };
+    let server_version = body["version"].as_str().map(str::to_string);
+    report(status, latency_ms, server_version, message)
+}
+
+/// Classify a failed request by reqwest's own flags and the I/O errors among its causes.
+/// A failed lookup shows up as an uncategorized I/O error, so telling DNS failures apart
+/// is left to [`resolves`].
+fn classify_request_error(e: &reqwest::Error) -> HealthStatus {
+    if e.is_timeout() {
+        return HealthStatus::Timeout;
+    }
+    if !e.is_connect() {
+        return HealthStatus::Error;
+    }
+    let kinds = io_error_kinds(e);
+    if kinds.contains(&io::ErrorKind::InvalidData) {
+        // The TLS layer reports handshake and certificate failures as invalid data
+        HealthStatus::TlsError
+    } else if kinds.contains(&io::ErrorKind::TimedOut) {
+        HealthStatus::Timeout
+    } else {
+        HealthStatus::Unreachable
+    }
+}
+
+/// Kinds of every I/O error in the cause chain of `e`.
+fn io_error_kinds(e: &reqwest::Error) -> Vec<io::ErrorKind> {
+    let mut kinds = Vec::new();
+    let mut source = e.source();
+    while let Some(cause) = source {
+        // I/O errors can wrap further I/O errors that `source()` skips over
+        let mut io = cause.downcast_ref::<io::Error>();
+        while let Some(error) = io {
+            kinds.push(error.kind());
+            io = error
+                .get_ref()
+                .and_then(|inner| inner.downcast_ref::<io::Error>());
+        }
+        source = cause.source();
+    }
+    kinds
+}
+
+/// Whether the host of `url` resolves to any address.
+async fn resolves(url: Option<&reqwest::Url>) -> bool {
+    let Some(addr) =
+        url.and_then(|url| Some((url.host_str()?.to_string(), url.port_or_known_default()?)))
+    else {
+        return true;
+    };
+    tokio::task::spawn_blocking(move || {
+        addr.to_socket_addrs()
+            .is_ok_and(|mut addrs| addrs.next().is_some())
+    })
+    .await
+    .unwrap_or(true)
+}
+
+fn error_message(status: HealthStatus, kind: &str, base_url: &str, e: &reqwest::Error) -> String {
+    match status {
+        HealthStatus::DnsError => format!(
+            "Could not resolve the host in {}. Check the base URL and your network.",
+            base_url
+        ),
+        HealthStatus::TlsError => format!(
+            "Secure connection to {} failed. Check the URL scheme (http or https) and the server's certificate.",
+            base_url
+        ),
+        HealthStatus::Timeout => format!(
+            "{} did not answer within {} seconds at {}.",
+            kind,
+            HEALTH_TIMEOUT.as_secs(),
+            base_url
+        ),
+        HealthStatus::Unreachable => format!(
+            "{} is not reachable at {}. Check that the server is running and the base URL in settings.",
+            kind, base_url
+        ),
+        _ => format!("Failed to reach {}: {}", kind, e),
+    }
+}
+
+fn response_error(body: &Value) -> String {
+    match &body["error"] {
+        Value::String(message) => message.clone(),
+        error => error["message"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| match body {
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::providers::test_server::{Reply, TestServer};
+    use serde_json::json;
+    use std::io::Write;
+    use std::net::TcpListener;
+
+    async fn check(url: &str) -> ProviderHealth {
+        let request = reqwest::Client::new().get(format!("{}/models", url));
+        check_health("Test", url, true, Ok(request)).await
+    }
+
+    async fn check_reply(reply: Reply) -> ProviderHealth {
+        let server = TestServer::start(vec![reply]);
+        check(&server.url).await
+    }
+
+    #[tokio::test]
+    async fn reports_a_reachable_server_and_its_version() {
+        let health = check_reply(Reply::json(200, json!({ "version": "0.9.0" }))).await;
+
+        assert_eq!(health.status, HealthStatus::Reachable);
+        assert_eq!(health.server_version.as_deref(), Some("0.9.0"));
+        assert!(health.latency_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn reports_rejected_keys() {
+        for status in [401, 403] {
+            let body = json!({ "error": { "message": "Invalid API key" } });
+            let health = check_reply(Reply::json(status, body)).await;
+            assert_eq!(health.status, HealthStatus::AuthFailed);
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_a_missing_api() {
+        let health = check_reply(Reply::json(404, json!({}))).await;
+
+        assert_eq!(health.status, HealthStatus::Error);
+        assert!(health.message.contains("API was not found"));
+    }
+
+    #[tokio::test]
+    async fn reports_a_closed_port_as_unreachable() {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let health = check(&format!("http://127.0.0.1:{}/v1", port)).await;
+
+        assert_eq!(health.status, HealthStatus::Unreachable);
+        assert!(health.latency_ms.is_none());
+    }
+
+    #[tokio::test]
+    async fn reports_an_unknown_host_as_a_dns_error() {
+        // .invalid never resolves (RFC 6761)
+        let health = check("http://provider.invalid/v1").await;
+
+        assert_eq!(health.status, HealthStatus::DnsError);
+    }
+
+    #[tokio::test]
+    async fn reports_a_silent_server_as_timed_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1/models", listener.local_addr().unwrap());
+        // Accepted by the OS backlog but never answered
+        let error = reqwest::Client::new()
+            .get(url)
+            .timeout(Duration::from_millis(200))
+            .send()
+            .await
+            .unwrap_err();
+
+        assert_eq!(classify_request_error(&error), HealthStatus::Timeout);
+        drop(listener);
+    }
+
+    #[tokio::test]
+    async fn reports_https_to_a_plain_server_as_a_tls_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("https://{}/v1", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            // Answer the TLS handshake in plain HTTP
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n");
+        });
+        let health = check(&url).await;
+
+        assert_eq!(health.status, HealthStatus::TlsError);
+    }
+}
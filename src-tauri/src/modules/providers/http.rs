@@ -0,0 +1,57 @@
+use crate::modules::error::{AppError, ErrorKind};
+use serde_json::Value;
+
+/// Turn a failed request into a readable error, calling out unreachable servers.
+pub fn describe_request_error(
+    e: &reqwest::Error,
+    kind: &str,
+    base_url: &str,
+    action: &str,
+) -> AppError {
+    if e.is_connect() || e.is_timeout() {
+        AppError::provider(
+            ErrorKind::Network,
+            kind,
+            format!(
+                "{} is not reachable at {}. Check your connection and the base URL in settings.",
+                kind, base_url
+            ),
+        )
+        .with_details(e.to_string())
+    } else {
+        AppError::provider(ErrorKind::Provider, kind, format!("{}: {}", action, e))
+    }
+}
+
+/// Read an error response, preferring the `error.message` most APIs return.
+pub async fn error_from_response(
+    response: reqwest::Response,
+    kind: &str,
+    action: &str,
+) -> AppError {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<Value>(&body)
+        .ok()
+        .and_then(|body| match &body["error"] {
+            Value::String(message) => Some(message.clone()),
+            error => error["message"].as_str().map(str::to_string),
+        })
+        .unwrap_or(body);
+    AppError::provider(
+        error_kind_for_status(status),
+        kind,
+        format!("{}: {} {}", action, status, message),
+    )
+    // Overloaded or briefly failing servers often recover
+    .with_retryable(status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS)
+}
+
+pub fn error_kind_for_status(status: reqwest::StatusCode) -> ErrorKind {
+    match status.as_u16() {
+        401 | 403 => ErrorKind::Auth,
+        404 => ErrorKind::NotFound,
+        429 => ErrorKind::RateLimited,
+        _ => ErrorKind::Provider,
+    }
+}
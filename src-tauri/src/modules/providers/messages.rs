@@ -0,0 +1,545 @@
+use super::types::ToolCall;
+use crate::modules::error::{AppError, AppResult, Context};
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use serde_json::Value;
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
+/// A chat message in a provider-neutral form, built from AI SDK messages.
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub role: Role,
+    pub content: Vec<Content>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Content {
+    Text(String),
+    Reasoning {
+        text: String,
+        signature: Option<String>, // Needed to send Anthropic thinking blocks back
+    },
+    Image(ImageData),
+    ToolCall(ToolCall),
+    ToolResult {
+        call_id: String,
+        name: String,
+        output: String,
+        is_error: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum ImageData {
+    Base64 { media_type: String, data: String },
+    Url(String), // Remote image, for providers that fetch it themselves
+}
+
+impl Message {
+    /// All text parts joined together.
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|content| match content {
+                Content::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl ImageData {
+    /// The image as a `data:` URL, or the remote URL itself.
+    pub fn to_url(&self) -> String {
+        match self {
+            ImageData::Base64 { media_type, data } => {
+                format!("data:{};base64,{}", media_type, data)
+            }
+            ImageData::Url(url) => url.clone(),
+        }
+    }
+}
+
+/// Convert an AI SDK message array into provider-neutral messages.
+///
+/// Accepts both UI messages (`parts`) and model messages (`content` as a string or
+/// an array of parts). Messages with an unknown role or no usable content are skipped;
+/// image parts that cannot be loaded are an error rather than being silently dropped.
+/// Tool calls stay on the assistant message that made them and their results follow
+/// in a `Tool` message, splitting UI messages at each `step-start`.
+pub fn normalize_messages(messages: &[Value]) -> AppResult<Vec<Message>> {
+    let mut out = Vec::new();
+    for message in messages {
+        append_ai_message(message, &mut out)?;
+    }
+    Ok(out)
+}
+
+/// Content collected for one step of a message before it is turned into messages.
+#[derive(Default)]
+struct PendingStep {
+    content: Vec<Content>,
+    tool_results: Vec<Content>,
+}
+
+impl PendingStep {
+    fn flush(&mut self, role: Role, out: &mut Vec<Message>) {
+        let step = std::mem::take(self);
+        if !step.content.is_empty() {
+            out.push(Message {
+                role,
+                content: step.content,
+            });
+        }
+        if !step.tool_results.is_empty() {
+            out.push(Message {
+                role: Role::Tool,
+                content: step.tool_results,
+            });
+        }
+    }
+}
+
+fn append_ai_message(message: &Value, out: &mut Vec<Message>) -> AppResult<()> {
+    let role = match message["role"].as_str() {
+        Some("system") => Role::System,
+        Some("user") => Role::User,
+        Some("assistant") => Role::Assistant,
+        Some("tool") => Role::Tool,
+        _ => return Ok(()),
+    };
+
+    let mut step = PendingStep::default();
+
+    let parts = match (&message["content"], &message["parts"]) {
+        (Value::String(content), _) => {
+            if !content.is_empty() {
+                step.content.push(Content::Text(content.clone()));
+            }
+            &[][..]
+        }
+        (Value::Array(parts), _) | (_, Value::Array(parts)) => parts.as_slice(),
+        _ => &[][..],
+    };
+
+    for part in parts {
+        let part_type = part["type"].as_str().unwrap_or_default();
+        match part_type {
+            "step-start" => step.flush(role, out),
+            "text" => {
+                let text = part["text"].as_str().unwrap_or_default();
+                if !text.is_empty() {
+                    step.content.push(Content::Text(text.to_string()));
+                }
+            }
+            "reasoning" => {
+                let text = part["text"].as_str().unwrap_or_default();
+                // UI messages keep provider data in providerMetadata, model messages
+                // in providerOptions
+                let signature = ["providerMetadata", "providerOptions"]
+                    .iter()
+                    .find_map(|key| part[key]["anthropic"]["signature"].as_str());
+                if !text.is_empty() || signature.is_some() {
+                    step.content.push(Content::Reasoning {
+                        text: text.to_string(),
+                        signature: signature.map(str::to_string),
+                    });
+                }
+            }
+            "image" => {
+                if let Some(source) = part["image"].as_str() {
+                    let media_type = part["mediaType"].as_str();
+                    step.content
+                        .push(Content::Image(load_image(source, media_type)?));
+                }
+            }
+            "file" => {
+                // Only images are passed on; other attachments are ignored
+                let media_type = part["mediaType"]
+                    .as_str()
+                    .filter(|media_type| media_type.starts_with("image/"));
+                let source = part["url"].as_str().or_else(|| part["data"].as_str());
+                if let (Some(source), Some(media_type)) = (source, media_type) {
+                    step.content
+                        .push(Content::Image(load_image(source, Some(media_type))?));
+                }
+            }
+            // Model message parts
+            "tool-call" => step.content.push(Content::ToolCall(tool_call(
+                part["toolCallId"].as_str(),
+                part["toolName"].as_str().unwrap_or_default(),
+                &part["input"],
+            ))),
+            "tool-result" => step.tool_results.push(Content::ToolResult {
+                call_id: part["toolCallId"].as_str().unwrap_or_default().to_string(),
+                name: part["toolName"].as_str().unwrap_or_default().to_string(),
+                output: tool_output_text(&part["output"]),
+                is_error: part["output"]["type"]
+                    .as_str()
+                    .is_some_and(|output_type| output_type.starts_with("error")),
+            }),
+            // UI message tool parts (`tool-<name>` or `dynamic-tool`) carry both the
+            // call and, once it has run, its output
+            _ if part_type == "dynamic-tool" || part_type.starts_with("tool-") => {
+                let name = part["toolName"]
+                    .as_str()
+                    .or_else(|| part_type.strip_prefix("tool-"))
+                    .unwrap_or_default();
+                let (output, is_error) = match part["state"].as_str() {
+                    Some("output-available") => (tool_output_text(&part["output"]), false),
+                    Some("output-error") => (
+                        format!(
+                            "Error: {}",
+                            part["errorText"].as_str().unwrap_or("tool failed")
+                        ),
+                        true,
+                    ),
+                    // Calls that never produced a result are left out; providers expect
+                    // every tool call to be answered
+                    _ => continue,
+                };
+                let call = tool_call(part["toolCallId"].as_str(), name, &part["input"]);
+                step.tool_results.push(Content::ToolResult {
+                    call_id: call.id.clone(),
+                    name: call.name.clone(),
+                    output,
+                    is_error,
+                });
+                step.content.push(Content::ToolCall(call));
+            }
+            _ => {}
+        }
+    }
+
+    step.flush(role, out);
+    Ok(())
+}
+
+fn tool_call(id: Option<&str>, name: &str, input: &Value) -> ToolCall {
+    ToolCall {
+        id: id
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple())),
+        name: name.to_string(),
+        arguments: match input {
+            Value::Null => Value::Object(Default::default()),
+            input => input.clone(),
+        },
+    }
+}
+
+/// Flatten a tool result (a plain value or an AI SDK `{ type, value }` output) to text.
+fn tool_output_text(output: &Value) -> String {
+    match (output, &output["value"]) {
+        (Value::String(value), _) | (_, Value::String(value)) => value.clone(),
+        (output, Value::Null) => output.to_string(),
+        (_, value) => value.to_string(),
+    }
+}
+
+/// Load an image part.
+///
+/// `data:` URLs and raw base64 are passed through; local paths and `file://` URLs are
+/// read from disk and encoded, but only when the file really is an image. Remote URLs are
+/// kept as URLs.
+fn load_image(source: &str, media_type: Option<&str>) -> AppResult<ImageData> {
+    let source = source.trim();
+    if source.is_empty() {
+        return Err(AppError::invalid_input("Image part has no data"));
+    }
+
+    if let Some(rest) = source.strip_prefix("data:") {
+        let (data_media_type, data) = rest
+            .split_once(";base64,")
+            .ok_or_else(|| AppError::invalid_input("Image data URLs must be base64 encoded"))?;
+        return Ok(ImageData::Base64 {
+            media_type: data_media_type.to_string(),
+            data: data.to_string(),
+        });
+    }
+
+    let path =
+        if source.starts_with("file://") {
+            let url = reqwest::Url::parse(source).map_err(|e| {
+                AppError::invalid_input(format!("Invalid image URL '{}': {}", source, e))
+            })?;
+            Some(url.to_file_path().map_err(|_| {
+                AppError::invalid_input(format!("Invalid image file URL '{}'", source))
+            })?)
+        } else if source.contains("://") {
+            return Ok(ImageData::Url(source.to_string()));
+        } else {
+            // Raw base64 can look like a path (JPEG data starts with "/9j/"), so only treat
+            // it as one when the file actually exists.
+            Some(PathBuf::from(source)).filter(|path| path.is_absolute() && path.is_file())
+        };
+
+    match path {
+        Some(path) => {
+            let action = format!("Failed to read image {}", path.display());
+            let mut file = File::open(&path).context(&action)?;
+            // Check the header before reading the rest, so no other file is ever loaded
+            let mut bytes = Vec::new();
+            (&mut file)
+                .take(IMAGE_HEADER_LEN)
+                .read_to_end(&mut bytes)
+                .context(&action)?;
+            let media_type = sniff_image_type(&bytes).ok_or_else(|| {
+                AppError::invalid_input(format!(
+                    "{} is not a PNG, JPEG, GIF or WebP image",
+                    path.display()
+                ))
+            })?;
+            file.read_to_end(&mut bytes).context(&action)?;
+            Ok(ImageData::Base64 {
+                media_type: media_type.to_string(),
+                data: BASE64_STANDARD.encode(bytes),
+            })
+        }
+        None => Ok(ImageData::Base64 {
+            media_type: media_type
+                .map(str::to_string)
+                .unwrap_or_else(|| media_type_for_base64(source)),
+            data: source.to_string(),
+        }),
+    }
+}
+
+/// Bytes needed to recognize every supported image format.
+const IMAGE_HEADER_LEN: u64 = 12;
+
+/// The media type of image data, from its magic bytes.
+pub fn sniff_image_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// Guess the media type of base64 image data from its leading bytes.
+fn media_type_for_base64(data: &str) -> String {
+    if data.starts_with("/9j/") {
+        "image/jpeg"
+    } else if data.starts_with("R0lGOD") {
+        "image/gif"
+    } else if data.starts_with("UklGR") {
+        "image/webp"
+    } else {
+        "image/png"
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::error::ErrorKind;
+    use serde_json::json;
+
+    fn temp_file(name: &str, bytes: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", uuid::Uuid::new_v4(), name));
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn sniffs_supported_image_types() {
+        assert_eq!(
+            sniff_image_type(b"\x89PNG\r\n\x1a\n...."),
+            Some("image/png")
+        );
+        assert_eq!(
+            sniff_image_type(&[0xff, 0xd8, 0xff, 0xe0]),
+            Some("image/jpeg")
+        );
+        assert_eq!(sniff_image_type(b"GIF89a......"), Some("image/gif"));
+        assert_eq!(
+            sniff_image_type(b"RIFF\0\0\0\0WEBPVP8 "),
+            Some("image/webp")
+        );
+        assert_eq!(sniff_image_type(b"RIFF\0\0\0\0WAVEfmt "), None);
+        assert_eq!(sniff_image_type(b""), None);
+    }
+
+    #[test]
+    fn loads_local_image_with_sniffed_media_type() {
+        let bytes = b"\x89PNG\r\n\x1a\nrest of the image";
+        let path = temp_file("image.jpg", bytes);
+        let image = load_image(path.to_str().unwrap(), Some("image/jpeg"));
+        std::fs::remove_file(&path).unwrap();
+
+        match image.unwrap() {
+            ImageData::Base64 { media_type, data } => {
+                assert_eq!(media_type, "image/png");
+                assert_eq!(BASE64_STANDARD.decode(data).unwrap(), bytes);
+            }
+            ImageData::Url(url) => panic!("expected base64 data, got {}", url),
+        }
+    }
+
+    #[test]
+    fn rejects_local_files_that_are_not_images() {
+        let path = temp_file("secrets.png", b"OPENAI_API_KEY=sk-...");
+        let url = reqwest::Url::from_file_path(&path).unwrap();
+        let by_path = load_image(path.to_str().unwrap(), Some("image/png"));
+        let by_url = load_image(url.as_str(), Some("image/png"));
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(by_path.unwrap_err().kind, ErrorKind::InvalidInput);
+        assert_eq!(by_url.unwrap_err().kind, ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn converts_ui_messages_splitting_tool_steps() {
+        let messages = normalize_messages(&[
+            json!({ "role": "system", "content": "Be brief." }),
+            json!({ "role": "user", "parts": [{ "type": "text", "text": "Weather in Oslo?" }] }),
+            json!({
+                "role": "assistant",
+                "parts": [
+                    { "type": "step-start" },
+                    {
+                        "type": "reasoning",
+                        "text": "Need the tool.",
+                        "providerMetadata": { "anthropic": { "signature": "sig" } }
+                    },
+                    {
+                        "type": "tool-get_weather",
+                        "toolCallId": "call_1",
+                        "state": "output-available",
+                        "input": { "city": "Oslo" },
+                        "output": "4°C"
+                    },
+                    {
+                        "type": "tool-get_time",
+                        "toolCallId": "call_2",
+                        "state": "input-available",
+                        "input": {}
+                    },
+                    { "type": "step-start" },
+                    { "type": "text", "text": "It is 4°C." }
+                ]
+            }),
+            json!({ "role": "data", "content": "ignored" }),
+        ])
+        .unwrap();
+
+        let roles: Vec<Role> = messages.iter().map(|message| message.role).collect();
+        assert_eq!(
+            roles,
+            [
+                Role::System,
+                Role::User,
+                Role::Assistant,
+                Role::Tool,
+                Role::Assistant
+            ]
+        );
+        assert_eq!(messages[1].text(), "Weather in Oslo?");
+        match &messages[2].content[..] {
+            [Content::Reasoning { text, signature }, Content::ToolCall(call)] => {
+                assert_eq!(text, "Need the tool.");
+                assert_eq!(signature.as_deref(), Some("sig"));
+                assert_eq!(call.id, "call_1");
+                assert_eq!(call.name, "get_weather");
+                assert_eq!(call.arguments, json!({ "city": "Oslo" }));
+            }
+            content => panic!("unexpected assistant content {:?}", content),
+        }
+        match &messages[3].content[..] {
+            [Content::ToolResult {
+                call_id,
+                name,
+                output,
+                is_error,
+            }] => {
+                assert_eq!(call_id, "call_1");
+                assert_eq!(name, "get_weather");
+                assert_eq!(output, "4°C");
+                assert!(!is_error);
+            }
+            content => panic!("unexpected tool content {:?}", content),
+        }
+        assert_eq!(messages[4].text(), "It is 4°C.");
+    }
+
+    #[test]
+    fn converts_model_messages() {
+        let messages = normalize_messages(&[
+            json!({
+                "role": "user",
+                "content": [
+                    { "type": "text", "text": "What is this?" },
+                    { "type": "image", "image": "data:image/png;base64,iVBORw0KGgo=" },
+                    { "type": "file", "mediaType": "application/pdf", "data": "JVBERi0=" }
+                ]
+            }),
+            json!({
+                "role": "assistant",
+                "content": [{ "type": "tool-call", "toolCallId": "call_1", "toolName": "lookup" }]
+            }),
+            json!({
+                "role": "tool",
+                "content": [{
+                    "type": "tool-result",
+                    "toolCallId": "call_1",
+                    "toolName": "lookup",
+                    "output": { "type": "error-text", "value": "not found" }
+                }]
+            }),
+            json!({ "role": "assistant", "content": "" }),
+        ])
+        .unwrap();
+
+        assert_eq!(messages.len(), 3);
+        match &messages[0].content[..] {
+            [Content::Text(text), Content::Image(image)] => {
+                assert_eq!(text, "What is this?");
+                assert_eq!(image.to_url(), "data:image/png;base64,iVBORw0KGgo=");
+            }
+            content => panic!("unexpected user content {:?}", content),
+        }
+        match &messages[1].content[..] {
+            [Content::ToolCall(call)] => assert_eq!(call.arguments, json!({})),
+            content => panic!("unexpected assistant content {:?}", content),
+        }
+        match &messages[2].content[..] {
+            [Content::ToolResult {
+                output, is_error, ..
+            }] => {
+                assert_eq!(output, "not found");
+                assert!(is_error);
+            }
+            content => panic!("unexpected tool content {:?}", content),
+        }
+    }
+
+    #[test]
+    fn guesses_media_type_of_raw_base64() {
+        match load_image("/9j/4AAQSkZJRg==", None).unwrap() {
+            ImageData::Base64 { media_type, .. } => assert_eq!(media_type, "image/jpeg"),
+            ImageData::Url(url) => panic!("expected base64 data, got {}", url),
+        }
+        assert!(matches!(
+            load_image("https://example.com/cat.png", None).unwrap(),
+            ImageData::Url(_)
+        ));
+        assert!(load_image("data:image/png,raw", None).is_err());
+    }
+}
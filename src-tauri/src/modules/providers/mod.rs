@@ -1,3 +1,37 @@
+pub mod anthropic;
+pub mod cache;
+pub mod capabilities;
+pub mod gemini;
+pub mod health;
+pub mod http;
+pub mod messages;
+pub mod modelfile;
+pub mod ndjson;
 pub mod ollama;
+pub mod openai;
+pub mod options;
+pub mod provider;
+pub mod registry;
+pub mod sse;
+#[cfg(test)]
+mod test_server;
+pub mod types;
 
-pub use ollama::OllamaProvider;
+pub use anthropic::AnthropicProvider;
+pub use cache::ModelCache;
+pub use capabilities::resolve_capabilities;
+pub use gemini::GeminiProvider;
+pub use health::{HealthStatus, ProviderHealth};
+pub use messages::{normalize_messages, Content, ImageData, Message, Role};
+pub use modelfile::{parse_modelfile, Modelfile};
+pub use ollama::{
+    history_from_ai_messages, ModelDetails, OllamaProvider, PullStatus, RunningModel,
+};
+pub use openai::OpenAiProvider;
+pub use options::GenerationOptions;
+pub use provider::Provider;
+pub use registry::{ProviderEntry, ProviderRegistry};
+pub use types::{
+    ChatEvent, ChatRequest, ChatStream, ModelCapabilities, ModelInfo, ToolCall, ToolDefinition,
+    Usage,
+};
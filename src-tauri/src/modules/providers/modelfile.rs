@@ -0,0 +1,228 @@
+use crate::modules::error::{AppError, AppResult, Context};
+use ollama_rs::{generation::chat::ChatMessage, models::ModelOptions};
+
+/// The parts of an Ollama Modelfile that can be sent to `/api/create`.
+#[derive(Debug, Default)]
+pub struct Modelfile {
+    pub from: Option<String>,
+    pub system: Option<String>,
+    pub template: Option<String>,
+    pub license: Vec<String>,
+    pub parameters: Option<ModelOptions>,
+    pub messages: Vec<ChatMessage>,
+}
+
+/// Parse Modelfile text (`FROM`, `SYSTEM`, `TEMPLATE`, `PARAMETER`, `LICENSE`, `MESSAGE`).
+///
+/// Values may be bare, `"quoted"` or `"""multi-line"""`. `ADAPTER` is rejected since
+/// adapters have to be uploaded as blobs first.
+pub fn parse_modelfile(text: &str) -> AppResult<Modelfile> {
+    let mut modelfile = Modelfile::default();
+    let mut options: Option<ModelOptions> = None;
+    let mut stop = Vec::new();
+    let mut lines = text.lines().enumerate();
+
+    while let Some((index, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (instruction, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        let value = if let Some(start) = rest.strip_prefix("\"\"\"") {
+            // Multi-line value, possibly closed on the same line
+            let mut value = String::new();
+            let mut current = start.to_string();
+            loop {
+                if let Some(end) = current.find("\"\"\"") {
+                    value.push_str(&current[..end]);
+                    break;
+                }
+                value.push_str(&current);
+                value.push('\n');
+                match lines.next() {
+                    Some((_, next)) => current = next.to_string(),
+                    None => {
+                        return Err(AppError::invalid_input(format!(
+                            "Line {}: unterminated \"\"\"",
+                            index + 1
+                        )))
+                    }
+                }
+            }
+            value
+        } else {
+            unquote(rest).to_string()
+        };
+
+        match instruction.to_ascii_uppercase().as_str() {
+            "FROM" => modelfile.from = Some(value),
+            "SYSTEM" => modelfile.system = Some(value),
+            "TEMPLATE" => modelfile.template = Some(value),
+            "LICENSE" => modelfile.license.push(value),
+            "PARAMETER" => {
+                let (name, raw) = value.split_once(char::is_whitespace).ok_or_else(|| {
+                    AppError::invalid_input(format!(
+                        "Line {}: PARAMETER needs a name and value",
+                        index + 1
+                    ))
+                })?;
+                let raw = unquote(raw.trim());
+                if name == "stop" {
+                    stop.push(raw.to_string());
+                    continue;
+                }
+                let current = options.take().unwrap_or_default();
+                options = Some(
+                    apply_parameter(current, name, raw).context(&format!("Line {}", index + 1))?,
+                );
+            }
+            "MESSAGE" => {
+                let (role, content) = value.split_once(char::is_whitespace).ok_or_else(|| {
+                    AppError::invalid_input(format!(
+                        "Line {}: MESSAGE needs a role and content",
+                        index + 1
+                    ))
+                })?;
+                let content = unquote(content.trim()).to_string();
+                modelfile.messages.push(match role {
+                    "system" => ChatMessage::system(content),
+                    "user" => ChatMessage::user(content),
+                    "assistant" => ChatMessage::assistant(content),
+                    other => {
+                        return Err(AppError::invalid_input(format!(
+                            "Line {}: unknown role '{}'",
+                            index + 1,
+                            other
+                        )))
+                    }
+                });
+            }
+            "ADAPTER" => {
+                return Err(AppError::invalid_input(format!(
+                    "Line {}: ADAPTER is not supported when creating models from the app",
+                    index + 1
+                )))
+            }
+            other => {
+                return Err(AppError::invalid_input(format!(
+                    "Line {}: unknown instruction '{}'",
+                    index + 1,
+                    other
+                )))
+            }
+        }
+    }
+
+    if !stop.is_empty() {
+        options = Some(options.unwrap_or_default().stop(stop));
+    }
+    modelfile.parameters = options;
+    Ok(modelfile)
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+fn apply_parameter(options: ModelOptions, name: &str, raw: &str) -> AppResult<ModelOptions> {
+    fn parse<T: std::str::FromStr>(name: &str, raw: &str) -> AppResult<T> {
+        raw.parse()
+            .map_err(|_| AppError::invalid_input(format!("invalid value '{}' for {}", raw, name)))
+    }
+
+    Ok(match name {
+        "mirostat" => options.mirostat(parse(name, raw)?),
+        "mirostat_eta" => options.mirostat_eta(parse(name, raw)?),
+        "mirostat_tau" => options.mirostat_tau(parse(name, raw)?),
+        "num_ctx" => options.num_ctx(parse(name, raw)?),
+        "num_gqa" => options.num_gqa(parse(name, raw)?),
+        "num_gpu" => options.num_gpu(parse(name, raw)?),
+        "num_thread" => options.num_thread(parse(name, raw)?),
+        "repeat_last_n" => options.repeat_last_n(parse(name, raw)?),
+        "repeat_penalty" => options.repeat_penalty(parse(name, raw)?),
+        "temperature" => options.temperature(parse(name, raw)?),
+        "seed" => options.seed(parse(name, raw)?),
+        "tfs_z" => options.tfs_z(parse(name, raw)?),
+        "num_predict" => options.num_predict(parse(name, raw)?),
+        "top_k" => options.top_k(parse(name, raw)?),
+        "top_p" => options.top_p(parse(name, raw)?),
+        other => {
+            return Err(AppError::invalid_input(format!(
+                "unsupported parameter '{}'",
+                other
+            )))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::error::ErrorKind;
+    use serde_json::json;
+
+    #[test]
+    fn parses_instructions_and_parameters() {
+        let modelfile = parse_modelfile(
+            r#"
+# A tuned assistant
+FROM llama3.2
+SYSTEM "You are terse."
+TEMPLATE """{{ .System }}
+{{ .Prompt }}"""
+PARAMETER temperature 0.2
+PARAMETER num_ctx 8192
+PARAMETER stop "<|eot_id|>"
+PARAMETER stop </s>
+MESSAGE user Hi
+MESSAGE assistant "Hello."
+LICENSE MIT
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(modelfile.from.as_deref(), Some("llama3.2"));
+        assert_eq!(modelfile.system.as_deref(), Some("You are terse."));
+        assert_eq!(
+            modelfile.template.as_deref(),
+            Some("{{ .System }}\n{{ .Prompt }}")
+        );
+        assert_eq!(modelfile.license, ["MIT"]);
+        let parameters = serde_json::to_value(modelfile.parameters.unwrap()).unwrap();
+        assert_eq!(parameters["num_ctx"], 8192);
+        assert_eq!(parameters["stop"], json!(["<|eot_id|>", "</s>"]));
+        assert!((parameters["temperature"].as_f64().unwrap() - 0.2).abs() < 1e-6);
+        let messages = serde_json::to_value(&modelfile.messages).unwrap();
+        assert_eq!(messages[0]["role"], "user");
+        assert_eq!(messages[0]["content"], "Hi");
+        assert_eq!(messages[1]["role"], "assistant");
+        assert_eq!(messages[1]["content"], "Hello.");
+    }
+
+    #[test]
+    fn rejects_invalid_lines_with_their_number() {
+        let cases = [
+            ("FROM llama3\nADAPTER ./lora.gguf", "Line 2"),
+            ("FROM llama3\nPARAMETER temperature hot", "Line 2"),
+            ("PARAMETER bogus 1", "Line 1"),
+            ("MESSAGE robot hi", "Line 1"),
+            ("FROM llama3\n\nRUN make", "Line 3"),
+            ("SYSTEM \"\"\"never closed", "Line 1"),
+        ];
+        for (text, line) in cases {
+            let error = parse_modelfile(text).unwrap_err();
+            assert_eq!(error.kind, ErrorKind::InvalidInput, "{}", text);
+            assert!(
+                error.message.starts_with(line),
+                "{}: {}",
+                text,
+                error.message
+            );
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use crate::modules::error::{AppError, AppResult, ErrorKind};
+use futures::{Stream, StreamExt};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::pin::Pin;
+
+pub type JsonLineStream = Pin<Box<dyn Stream<Item = AppResult<Value>> + Send>>;
+
+/// Split a streaming newline-delimited JSON body (e.g. Ollama's `/api/pull`) into values.
+///
+/// A chunk may hold several lines or only part of one, so lines are buffered and only
+/// parsed once complete. Blank lines are skipped.
+pub fn json_lines(response: reqwest::Response) -> JsonLineStream {
+    parse_json_lines(response.bytes_stream())
+}
+
+/// Split a stream of body chunks into JSON values, one per line.
+fn parse_json_lines<S, B, E>(body: S) -> JsonLineStream
+where
+    S: Stream<Item = Result<B, E>> + Send + 'static,
+    B: AsRef<[u8]>,
+    E: Display,
+{
+    struct State<S> {
+        body: Pin<Box<S>>,
+        buffer: Vec<u8>,
+        ready: VecDeque<AppResult<Value>>,
+        done: bool,
+    }
+
+    impl<S> State<S> {
+        fn take_lines(&mut self) {
+            while let Some(end) = self.buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                self.line(&line);
+            }
+        }
+
+        fn line(&mut self, line: &[u8]) {
+            let line = String::from_utf8_lossy(line);
+            let line = line.trim();
+            if line.is_empty() {
+                return;
+            }
+            self.ready
+                .push_back(serde_json::from_str(line).map_err(|e| {
+                    AppError::new(
+                        ErrorKind::Serialization,
+                        format!("Invalid JSON line in stream: {}", e),
+                    )
+                    .with_details(line.to_string())
+                }));
+        }
+    }
+
+    let state = State {
+        body: Box::pin(body),
+        buffer: Vec::new(),
+        ready: VecDeque::new(),
+        done: false,
+    };
+
+    Box::pin(futures::stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(value) = state.ready.pop_front() {
+                return Some((value, state));
+            }
+            if state.done {
+                return None;
+            }
+            match state.body.next().await {
+                Some(Ok(chunk)) => {
+                    state.buffer.extend_from_slice(chunk.as_ref());
+                    state.take_lines();
+                }
+                Some(Err(e)) => {
+                    state.done = true;
+                    let error = AppError::new(
+                        ErrorKind::Network,
+                        format!("Connection lost while streaming: {}", e),
+                    );
+                    return Some((Err(error), state));
+                }
+                None => {
+                    // The last line may lack its newline
+                    state.done = true;
+                    let rest = std::mem::take(&mut state.buffer);
+                    state.line(&rest);
+                }
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    async fn parse(chunks: &[&[u8]]) -> Vec<AppResult<Value>> {
+        let chunks: Vec<Result<Vec<u8>, String>> =
+            chunks.iter().map(|chunk| Ok(chunk.to_vec())).collect();
+        parse_json_lines(futures::stream::iter(chunks))
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn splits_lines_across_and_within_chunks() {
+        let values = parse(&[
+            b"{\"status\":\"pulling manifest\"}\n{\"status\":\"down",
+            b"loading\",\"completed\":1}\n\n{\"status\":\"success\"}",
+        ])
+        .await;
+        let values: Vec<Value> = values.into_iter().map(Result::unwrap).collect();
+
+        assert_eq!(
+            values,
+            [
+                json!({ "status": "pulling manifest" }),
+                json!({ "status": "downloading", "completed": 1 }),
+                json!({ "status": "success" }),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_invalid_lines() {
+        let values = parse(&[b"{\"status\":\"ok\"}\nnot json\n"]).await;
+
+        assert_eq!(values.len(), 2);
+        assert!(values[0].is_ok());
+        assert_eq!(
+            values[1].as_ref().unwrap_err().kind,
+            ErrorKind::Serialization
+        );
+    }
+}
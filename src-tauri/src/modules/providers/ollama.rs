@@ -1,15 +1,204 @@
+use super::http::error_from_response;
+use super::messages::{normalize_messages, Content, ImageData, Role};
+use super::modelfile::Modelfile;
+use super::ndjson::json_lines;
+use super::options::GenerationOptions;
+use super::provider::Provider;
+use super::types::{
+    ChatEvent, ChatRequest, ChatStream, ModelCapabilities, ModelInfo, ToolCall, ToolDefinition,
+    Usage,
+};
+use crate::modules::error::{AppError, AppResult, ErrorKind};
+use crate::modules::settings::AppSettings;
+use async_trait::async_trait;
 use futures::{Stream, StreamExt};
 use ollama_rs::{
-    generation::chat::{request::ChatMessageRequest, ChatMessage},
+    error::OllamaError,
+    generation::{
+        chat::{request::ChatMessageRequest, ChatMessage, MessageRole},
+        images::Image,
+        tools::{
+            ToolCall as OllamaToolCall, ToolCallFunction, ToolFunctionInfo, ToolInfo, ToolType,
+        },
+    },
+    headers::{HeaderMap, HeaderValue, AUTHORIZATION},
+    models::create::CreateModelRequest,
     Ollama,
 };
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
 use std::pin::Pin;
-use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 
+pub const DEFAULT_OLLAMA_BASE_URL: &str = "http://127.0.0.1:11434";
+
 pub struct OllamaProvider {
     client: Ollama,
+    // Used for endpoints ollama-rs does not cover (e.g. /api/ps)
+    http: reqwest::Client,
+    api_key: Option<String>,
+}
+
+/// A model currently loaded in memory, as reported by `/api/ps`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunningModel {
+    pub name: String,
+    #[serde(default)]
+    pub model: String,
+    #[serde(default)]
+    pub digest: String,
+    pub size: u64, // Total memory footprint in bytes
+    #[serde(default)]
+    pub size_vram: u64, // Portion held in GPU memory
+    pub expires_at: String,
+    pub context_length: Option<u64>,
+}
+
+/// One progress line of `/api/pull`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PullStatus {
+    #[serde(default)]
+    pub status: String, // e.g. "pulling manifest", "downloading", "success"
+    pub digest: Option<String>, // Layer being downloaded, if any
+    pub total: Option<u64>,
+    pub completed: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RunningModelsResponse {
+    #[serde(default)]
+    models: Vec<RunningModel>,
+}
+
+/// Metadata for a local model, from `/api/show`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelDetails {
+    pub name: String,
+    pub family: Option<String>,
+    pub families: Vec<String>,
+    pub format: Option<String>,         // e.g. "gguf"
+    pub parameter_size: Option<String>, // e.g. "8.0B"
+    pub parameter_count: Option<u64>,
+    pub quantization_level: Option<String>, // e.g. "Q4_K_M"
+    pub context_length: Option<u64>,
+    pub capabilities: Vec<String>, // e.g. "completion", "vision", "tools", "thinking"
+    pub supports_vision: bool,
+    pub supports_tools: bool,
+    pub license: Option<String>,
+    pub template: Option<String>,
+    pub system: Option<String>,
+    pub parameters: BTreeMap<String, Vec<String>>, // Modelfile PARAMETERs; `stop` may repeat
+    pub modified_at: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ShowResponse {
+    #[serde(default)]
+    license: String,
+    #[serde(default)]
+    parameters: String,
+    #[serde(default)]
+    template: String,
+    #[serde(default)]
+    system: String,
+    #[serde(default)]
+    details: ShowDetails,
+    #[serde(default)]
+    model_info: serde_json::Map<String, Value>,
+    #[serde(default)]
+    capabilities: Vec<String>,
+    modified_at: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ShowDetails {
+    format: Option<String>,
+    family: Option<String>,
+    families: Option<Vec<String>>,
+    parameter_size: Option<String>,
+    quantization_level: Option<String>,
+}
+
+impl ModelDetails {
+    pub fn to_model_info(&self) -> ModelInfo {
+        ModelInfo {
+            name: self.name.clone(),
+            capabilities: ModelCapabilities {
+                context_length: self.context_length,
+                vision: Some(self.supports_vision),
+                tools: Some(self.supports_tools),
+                json_mode: Some(true), // Any model can be constrained with `format`
+                reasoning: Some(self.capabilities.iter().any(|c| c == "thinking")),
+                input_price: Some(0.0), // Runs locally
+                output_price: Some(0.0),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn from_show(name: &str, show: ShowResponse) -> Self {
+        let info = &show.model_info;
+        let architecture = info
+            .get("general.architecture")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let context_length = architecture
+            .as_ref()
+            .and_then(|arch| info.get(&format!("{}.context_length", arch)))
+            .and_then(Value::as_u64);
+
+        let families = show.details.families.unwrap_or_default();
+        let mut capabilities = show.capabilities;
+        // Servers older than the `capabilities` field: infer from the projector and template
+        if capabilities.is_empty() {
+            capabilities.push("completion".to_string());
+            let has_projector = info.keys().any(|key| key.contains(".vision."))
+                || families.iter().any(|f| f == "clip" || f == "mllama");
+            if has_projector {
+                capabilities.push("vision".to_string());
+            }
+            if show.template.contains(".Tools") {
+                capabilities.push("tools".to_string());
+            }
+        }
+
+        let mut parameters: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for line in show.parameters.lines() {
+            if let Some((key, value)) = line.trim().split_once(char::is_whitespace) {
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .unwrap_or(value);
+                parameters
+                    .entry(key.to_string())
+                    .or_default()
+                    .push(value.to_string());
+            }
+        }
+
+        let non_empty = |value: String| Some(value).filter(|v| !v.trim().is_empty());
+        Self {
+            name: name.to_string(),
+            family: show.details.family.or(architecture),
+            families,
+            format: show.details.format,
+            parameter_size: show.details.parameter_size,
+            parameter_count: info.get("general.parameter_count").and_then(Value::as_u64),
+            quantization_level: show.details.quantization_level,
+            context_length,
+            supports_vision: capabilities.iter().any(|c| c == "vision"),
+            supports_tools: capabilities.iter().any(|c| c == "tools"),
+            capabilities,
+            license: non_empty(show.license),
+            template: non_empty(show.template),
+            system: non_empty(show.system),
+            parameters,
+            modified_at: show.modified_at,
+        }
+    }
 }
 
 impl Default for OllamaProvider {
@@ -22,72 +211,200 @@ impl OllamaProvider {
     pub fn new() -> Self {
         Self {
             client: Ollama::default(),
+            http: reqwest::Client::new(),
+            api_key: None,
+        }
+    }
+
+    /// Connect to a custom Ollama endpoint, optionally authenticating with a bearer token
+    /// (e.g. an Ollama instance behind a reverse proxy).
+    pub fn with_config(base_url: Option<&str>, api_key: Option<&str>) -> AppResult<Self> {
+        let base_url = base_url
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .unwrap_or(DEFAULT_OLLAMA_BASE_URL);
+        // Requests are built as `{base}api/...`, so the base must end with a slash
+        let base_url = format!("{}/", base_url.trim_end_matches('/'));
+        let mut client = Ollama::try_new(base_url.as_str()).map_err(|e| {
+            AppError::invalid_input(format!("Invalid Ollama URL '{}': {}", base_url, e))
+                .with_provider("Ollama")
+        })?;
+
+        let api_key = api_key
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(str::to_string);
+        if let Some(api_key) = &api_key {
+            let mut value = HeaderValue::from_str(&format!("Bearer {}", api_key)).map_err(|e| {
+                AppError::invalid_input(format!("Invalid Ollama API key: {}", e))
+                    .with_provider("Ollama")
+            })?;
+            value.set_sensitive(true);
+            let mut headers = HeaderMap::new();
+            headers.insert(AUTHORIZATION, value);
+            client.set_headers(Some(headers));
         }
+
+        Ok(Self {
+            client,
+            http: reqwest::Client::new(),
+            api_key,
+        })
     }
 
-    pub async fn stream_chat(
+    /// Build a provider from the Ollama URL and key stored in app settings.
+    pub fn from_settings(settings: &AppSettings) -> AppResult<Self> {
+        Self::with_config(
+            settings.ollama_base_url.as_deref(),
+            settings.ollama_api_key.as_deref(),
+        )
+    }
+
+    fn describe_error(&self, e: &OllamaError, action: &str) -> AppError {
+        match e {
+            OllamaError::ReqwestError(e) => self.describe_http_error(e, action),
+            _ => AppError::provider(ErrorKind::Provider, "Ollama", format!("{}: {}", action, e)),
+        }
+    }
+
+    fn describe_http_error(&self, e: &reqwest::Error, action: &str) -> AppError {
+        if e.is_connect() || e.is_timeout() {
+            AppError::provider(
+                ErrorKind::Network,
+                "Ollama",
+                format!(
+                    "Ollama is not reachable at {}. Please start Ollama with 'ollama serve' or check the Ollama URL in settings.",
+                    self.base_url()
+                ),
+            )
+            .with_details(e.to_string())
+        } else {
+            AppError::provider(ErrorKind::Provider, "Ollama", format!("{}: {}", action, e))
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .http
+            .request(method, format!("{}{}", self.base_url(), path));
+        match &self.api_key {
+            Some(api_key) => request.bearer_auth(api_key),
+            None => request,
+        }
+    }
+
+    /// Send a raw request and decode the JSON body, mapping failures to readable errors.
+    async fn send_json<T: serde::de::DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+        action: &str,
+    ) -> AppResult<T> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| self.describe_http_error(&e, action))?;
+        if !response.status().is_success() {
+            return Err(error_from_response(response, "Ollama", action).await);
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| self.describe_http_error(&e, action))
+    }
+
+    /// Stream a chat over an already converted Ollama history.
+    pub async fn stream_messages(
         &self,
         model: &str,
-        user_message: &str,
-    ) -> Result<
-        Pin<
-            Box<dyn Stream<Item = Result<String, Box<dyn std::error::Error + Send + Sync>>> + Send>,
-        >,
-        Box<dyn std::error::Error + Send + Sync>,
-    > {
+        messages: Vec<ChatMessage>,
+        options: &GenerationOptions,
+        tools: &[ToolDefinition],
+    ) -> AppResult<ChatStream> {
+        let tools = tools
+            .iter()
+            .map(ollama_tool_info)
+            .collect::<AppResult<Vec<_>>>()?;
+
         // Create a channel for streaming tokens
-        let (tx, rx) =
-            mpsc::channel::<Result<String, Box<dyn std::error::Error + Send + Sync>>>(100);
+        let (tx, rx) = mpsc::channel::<AppResult<ChatEvent>>(100);
 
-        let client = self.client.clone();
+        let provider = self.clone();
         let model = model.to_string();
-        let user_message = user_message.to_string();
+        let options = options.clone();
 
         // Spawn the chat task
         tokio::spawn(async move {
-            let history = Arc::new(Mutex::new(vec![]));
-
-            let chat_request = ChatMessageRequest::new(
-                model.clone(),
-                vec![ChatMessage::user(user_message.clone())],
-            );
+            let mut chat_request = ChatMessageRequest::new(model, messages);
+            if !options.is_empty() {
+                chat_request = chat_request.options(options.to_ollama());
+            }
+            if !tools.is_empty() {
+                chat_request = chat_request.tools(tools);
+            }
 
-            match client
-                .send_chat_messages_with_history_stream(history.clone(), chat_request)
+            match provider
+                .client
+                .send_chat_messages_stream(chat_request)
                 .await
             {
-                Ok(mut stream) => {
-                    while let Some(response) = stream.next().await {
-                        match response {
-                            Ok(chat_response) => {
-                                // Send the content as a token
-                                if let Err(_) = tx.send(Ok(chat_response.message.content)).await {
-                                    break; // Receiver was dropped
+                Ok(mut stream) => loop {
+                    // Stop pulling from Ollama as soon as the consumer goes away (e.g. cancelled)
+                    let response = tokio::select! {
+                        response = stream.next() => response,
+                        _ = tx.closed() => break,
+                    };
+                    let Some(response) = response else {
+                        break;
+                    };
+                    match response {
+                        Ok(chat_response) => {
+                            let message = chat_response.message;
+                            let mut events = Vec::new();
+                            if let Some(thinking) = message.thinking.filter(|t| !t.is_empty()) {
+                                events.push(ChatEvent::Reasoning(thinking));
+                            }
+                            if !message.content.is_empty() {
+                                events.push(ChatEvent::Text(message.content));
+                            }
+                            // Ollama has no call ids, so generate one per call for results to refer to
+                            events.extend(message.tool_calls.into_iter().map(|call| {
+                                ChatEvent::ToolCall(ToolCall {
+                                    id: format!("call_{}", uuid::Uuid::new_v4().simple()),
+                                    name: call.function.name,
+                                    arguments: call.function.arguments,
+                                })
+                            }));
+                            if let Some(final_data) = chat_response.final_data {
+                                events.push(ChatEvent::Usage(Usage {
+                                    input_tokens: final_data.prompt_eval_count,
+                                    output_tokens: final_data.eval_count,
+                                }));
+                            }
+                            let mut closed = false;
+                            for event in events {
+                                if tx.send(Ok(event)).await.is_err() {
+                                    closed = true; // Receiver was dropped
+                                    break;
                                 }
                             }
-                            Err(e) => {
-                                let error_msg = format!("Ollama error: {:?}", e);
-                                let _ = tx
-                                    .send(Err(Box::new(std::io::Error::other(error_msg))
-                                        as Box<dyn std::error::Error + Send + Sync>))
-                                    .await;
+                            if closed {
                                 break;
                             }
                         }
+                        Err(e) => {
+                            let error = AppError::provider(
+                                ErrorKind::Provider,
+                                "Ollama",
+                                format!("Ollama error: {:?}", e),
+                            );
+                            let _ = tx.send(Err(error)).await;
+                            break;
+                        }
                     }
-                }
+                },
                 Err(e) => {
-                    let error_msg = if e.to_string().contains("connection")
-                        || e.to_string().contains("Connection refused")
-                    {
-                        "Ollama is not running. Please start Ollama first.".to_string()
-                    } else {
-                        format!("Failed to start Ollama stream: {}", e)
-                    };
-                    let _ = tx
-                        .send(Err(Box::new(std::io::Error::other(error_msg))
-                            as Box<dyn std::error::Error + Send + Sync>))
-                        .await;
+                    let error = provider.describe_error(&e, "Failed to start Ollama stream");
+                    let _ = tx.send(Err(error)).await;
                 }
             }
         });
@@ -96,11 +413,162 @@ impl OllamaProvider {
         Ok(Box::pin(ReceiverStream::new(rx)))
     }
 
-    pub async fn list_models(&self) -> Result<Vec<String>, String> {
+    /// Download a model from the Ollama registry, yielding progress updates as they arrive.
+    ///
+    /// The stream ends with an error if Ollama reports one or stops before its final
+    /// `success` line.
+    pub async fn pull_model(
+        &self,
+        model_name: &str,
+        allow_insecure: bool,
+    ) -> AppResult<Pin<Box<dyn Stream<Item = AppResult<PullStatus>> + Send>>> {
+        let action = "Failed to pull model";
+        let response = self
+            .request(reqwest::Method::POST, "api/pull")
+            .json(&serde_json::json!({
+                "model": model_name,
+                "insecure": allow_insecure,
+                "stream": true,
+            }))
+            .send()
+            .await
+            .map_err(|e| self.describe_http_error(&e, action))?;
+        if !response.status().is_success() {
+            return Err(error_from_response(response, "Ollama", action).await);
+        }
+
+        let lines = json_lines(response).map(move |line| {
+            let line = line.map_err(|e| e.with_provider("Ollama"))?;
+            if let Some(error) = line["error"].as_str() {
+                return Err(AppError::provider(
+                    ErrorKind::Provider,
+                    "Ollama",
+                    format!("{}: {}", action, error),
+                ));
+            }
+            serde_json::from_value::<PullStatus>(line).map_err(|e| {
+                AppError::provider(
+                    ErrorKind::Serialization,
+                    "Ollama",
+                    format!("{}: unexpected progress line: {}", action, e),
+                )
+            })
+        });
+
+        // Stop after the first error; a body ending without `success` is a failed pull
+        let statuses = futures::stream::unfold(
+            (Box::pin(lines), false),
+            move |(mut lines, finished)| async move {
+                if finished {
+                    return None;
+                }
+                match lines.next().await {
+                    Some(Ok(status)) => {
+                        let finished = status.status == "success";
+                        Some((Ok(status), (lines, finished)))
+                    }
+                    Some(Err(error)) => Some((Err(error), (lines, true))),
+                    None => Some((
+                        Err(AppError::provider(
+                            ErrorKind::Network,
+                            "Ollama",
+                            format!("{}: Ollama stopped before the download finished", action),
+                        )),
+                        (lines, true),
+                    )),
+                }
+            },
+        );
+        Ok(Box::pin(statuses))
+    }
+
+    pub async fn delete_model(&self, model_name: &str) -> AppResult<()> {
+        self.client
+            .delete_model(model_name.to_string())
+            .await
+            .map_err(|e| self.describe_error(&e, "Failed to delete model"))
+    }
+
+    pub async fn copy_model(&self, source: &str, destination: &str) -> AppResult<()> {
+        self.client
+            .copy_model(source.to_string(), destination.to_string())
+            .await
+            .map_err(|e| self.describe_error(&e, "Failed to copy model"))
+    }
+
+    /// Create a model from a parsed Modelfile, returning Ollama's final status message.
+    pub async fn create_model(&self, name: &str, modelfile: Modelfile) -> AppResult<String> {
+        let from = modelfile.from.ok_or_else(|| {
+            AppError::invalid_input("A base model (FROM) is required to create a model")
+        })?;
+        let mut request = CreateModelRequest::new(name.to_string()).from_model(from);
+        if let Some(system) = modelfile.system {
+            request = request.system(system);
+        }
+        if let Some(template) = modelfile.template {
+            request = request.template(template);
+        }
+        if !modelfile.license.is_empty() {
+            request = request.licenses(modelfile.license);
+        }
+        if let Some(parameters) = modelfile.parameters {
+            request = request.parameters(parameters);
+        }
+        if !modelfile.messages.is_empty() {
+            request = request.messages(modelfile.messages);
+        }
+
+        self.client
+            .create_model(request)
+            .await
+            .map(|status| status.message)
+            .map_err(|e| self.describe_error(&e, "Failed to create model"))
+    }
+
+    /// Models currently loaded into memory, with their footprint and unload time.
+    pub async fn list_running_models(&self) -> AppResult<Vec<RunningModel>> {
+        let response: RunningModelsResponse = self
+            .send_json(
+                self.request(reqwest::Method::GET, "api/ps"),
+                "Failed to list running models",
+            )
+            .await?;
+        Ok(response.models)
+    }
+
+    /// Get detailed information about a specific model
+    pub async fn model_details(&self, model_name: &str) -> AppResult<ModelDetails> {
+        let request = self
+            .request(reqwest::Method::POST, "api/show")
+            .json(&serde_json::json!({ "model": model_name }));
+        let show: ShowResponse = self.send_json(request, "Failed to get model info").await?;
+        Ok(ModelDetails::from_show(model_name, show))
+    }
+}
+
+#[async_trait]
+impl Provider for OllamaProvider {
+    fn kind(&self) -> &str {
+        "Ollama"
+    }
+
+    fn base_url(&self) -> &str {
+        self.client.url_str()
+    }
+
+    fn is_configured(&self) -> bool {
+        true // Runs locally, no API key needed
+    }
+
+    async fn list_models(&self) -> AppResult<Vec<String>> {
         match self.client.list_local_models().await {
             Ok(models) => {
                 if models.is_empty() {
-                    return Err("No models found. Please pull some models first using 'ollama pull <model-name>'.".to_string());
+                    return Err(AppError::provider(
+                        ErrorKind::NotFound,
+                        "Ollama",
+                        "No models found. Please pull a model first, either from the app or with 'ollama pull <model-name>'.",
+                    ));
                 }
 
                 let model_names: Vec<String> = models
@@ -118,41 +586,210 @@ impl OllamaProvider {
                 println!("Total Ollama models found: {}", model_names.len());
                 Ok(model_names)
             }
-            Err(e) => {
-                // Check if it's a connection error (Ollama not running)
-                let error_str = e.to_string();
-                if error_str.contains("connection")
-                    || error_str.contains("Connection refused")
-                    || error_str.contains("ConnectError")
-                    || error_str.contains("11434")
-                {
-                    Err(
-                        "Ollama is not running. Please start Ollama with 'ollama serve' first."
-                            .to_string(),
-                    )
-                } else {
-                    Err(format!("Failed to list Ollama models: {}", e))
+            Err(e) => Err(self.describe_error(&e, "Failed to list Ollama models")),
+        }
+    }
+
+    async fn list_model_info(&self) -> AppResult<Vec<ModelInfo>> {
+        // Capabilities need /api/show per model; local servers answer these quickly
+        let names = self.list_models().await?;
+        Ok(
+            futures::future::join_all(names.into_iter().map(|name| async move {
+                match self.model_details(&name).await {
+                    Ok(details) => details.to_model_info(),
+                    Err(_) => ModelInfo::named(name),
+                }
+            }))
+            .await,
+        )
+    }
+
+    async fn stream_chat(&self, request: ChatRequest) -> AppResult<ChatStream> {
+        // Translate the full AI SDK conversation (system prompt, prior turns, tool results)
+        let history = history_from_ai_messages(&request.messages)?;
+        if history.is_empty() {
+            return Err(AppError::invalid_input("No messages to send to Ollama"));
+        }
+
+        // Fail early with a clear message instead of letting a text-only model ignore images
+        let has_images = history
+            .iter()
+            .any(|m| m.images.as_ref().is_some_and(|images| !images.is_empty()));
+        if has_images {
+            if let Ok(details) = self.model_details(&request.model).await {
+                if !details.supports_vision {
+                    return Err(AppError::invalid_input(format!(
+                        "{} does not support image input. Choose a vision model such as llava or llama3.2-vision.",
+                        request.model
+                    ))
+                    .with_provider("Ollama"));
                 }
             }
         }
+
+        self.stream_messages(&request.model, history, &request.options, &request.tools)
+            .await
     }
 
-    /// Get detailed information about a specific model
-    pub async fn get_model_info(&self, model_name: &str) -> Result<String, String> {
-        match self.client.show_model_info(model_name.to_string()).await {
-            Ok(info) => Ok(format!(
-                "Model: {}\nParameters: {}\nTemplate: {}",
-                model_name, info.parameters, info.template
-            )),
-            Err(e) => Err(format!("Failed to get model info: {}", e)),
+    async fn model_info(&self, model: &str) -> AppResult<ModelInfo> {
+        Ok(self.model_details(model).await?.to_model_info())
+    }
+
+    // Also reports the server version
+    fn health_request(&self) -> AppResult<reqwest::RequestBuilder> {
+        Ok(self.request(reqwest::Method::GET, "api/version"))
+    }
+}
+
+/// Convert an AI SDK message array into an Ollama chat history.
+///
+/// See `normalize_messages` for how the messages are read. Ollama only accepts inline
+/// image data, so remote image URLs are an error.
+pub fn history_from_ai_messages(messages: &[Value]) -> AppResult<Vec<ChatMessage>> {
+    let mut history = Vec::new();
+    for message in normalize_messages(messages)? {
+        if message.role == Role::Tool {
+            // Ollama takes one tool message per result
+            let text = message.text();
+            if !text.is_empty() {
+                history.push(ChatMessage::tool(text));
+            }
+            history.extend(
+                message
+                    .content
+                    .into_iter()
+                    .filter_map(|content| match content {
+                        Content::ToolResult { output, .. } => Some(ChatMessage::tool(output)),
+                        _ => None,
+                    }),
+            );
+            continue;
+        }
+
+        let role = match message.role {
+            Role::System => MessageRole::System,
+            Role::User => MessageRole::User,
+            _ => MessageRole::Assistant,
+        };
+        let mut chat_message = ChatMessage::new(role, message.text());
+        let mut thinking = String::new();
+        let mut images = Vec::new();
+        for content in message.content {
+            match content {
+                Content::Reasoning { text, .. } => thinking.push_str(&text),
+                Content::Image(ImageData::Base64 { data, .. }) => {
+                    images.push(Image::from_base64(data))
+                }
+                Content::Image(ImageData::Url(url)) => {
+                    return Err(AppError::invalid_input(format!(
+                        "Remote image URLs are not supported for Ollama models ({}). Attach the image instead.",
+                        url
+                    )))
+                }
+                Content::ToolCall(call) => chat_message.tool_calls.push(OllamaToolCall {
+                    function: ToolCallFunction {
+                        name: call.name,
+                        arguments: call.arguments,
+                    },
+                }),
+                _ => {}
+            }
         }
+        if !images.is_empty() {
+            chat_message = chat_message.with_images(images);
+        }
+        if !thinking.is_empty() {
+            chat_message.thinking = Some(thinking);
+        }
+        history.push(chat_message);
     }
+    Ok(history)
+}
+
+/// Build the ollama-rs tool description; its parameters are a JSON Schema value.
+fn ollama_tool_info(tool: &ToolDefinition) -> AppResult<ToolInfo> {
+    let function: ToolFunctionInfo = serde_json::from_value(serde_json::json!({
+        "name": tool.name,
+        "description": tool.description,
+        "parameters": tool.parameters,
+    }))
+    .map_err(|e| {
+        AppError::invalid_input(format!(
+            "Invalid parameters schema for tool '{}': {}",
+            tool.name, e
+        ))
+    })?;
+    Ok(ToolInfo {
+        tool_type: ToolType::Function,
+        function,
+    })
 }
 
 impl Clone for OllamaProvider {
     fn clone(&self) -> Self {
         Self {
             client: self.client.clone(),
+            http: self.http.clone(),
+            api_key: self.api_key.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::providers::test_server::{Reply, TestServer};
+
+    async fn pull(reply: Reply) -> Vec<AppResult<PullStatus>> {
+        let server = TestServer::start(vec![reply]);
+        let provider = OllamaProvider::with_config(Some(&server.url), None).unwrap();
+        let statuses = provider
+            .pull_model("llama3.2", false)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        let request = server.received();
+        assert!(request.path.ends_with("/api/pull"));
+        assert_eq!(request.body["model"], "llama3.2");
+        statuses
+    }
+
+    #[tokio::test]
+    async fn pulls_until_success() {
+        let statuses = pull(Reply::ndjson(&[
+            r#"{"status":"pulling manifest"}"#,
+            r#"{"status":"downloading","digest":"sha256:ab","total":10,"completed":4}"#,
+            r#"{"status":"success"}"#,
+        ]))
+        .await;
+        let statuses: Vec<PullStatus> = statuses.into_iter().map(Result::unwrap).collect();
+
+        assert_eq!(statuses.len(), 3);
+        assert_eq!(statuses[1].digest.as_deref(), Some("sha256:ab"));
+        assert_eq!(statuses[1].completed, Some(4));
+        assert_eq!(statuses[2].status, "success");
+    }
+
+    #[tokio::test]
+    async fn reports_error_lines() {
+        let statuses = pull(Reply::ndjson(&[
+            r#"{"status":"pulling manifest"}"#,
+            r#"{"error":"pull model manifest: file does not exist"}"#,
+        ]))
+        .await;
+
+        assert_eq!(statuses.len(), 2);
+        let error = statuses[1].as_ref().unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Provider);
+        assert!(error.message.contains("file does not exist"));
+    }
+
+    #[tokio::test]
+    async fn fails_a_pull_that_ends_without_success() {
+        let statuses = pull(Reply::ndjson(&[r#"{"status":"downloading"}"#])).await;
+
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[1].as_ref().unwrap_err().kind, ErrorKind::Network);
+    }
+}
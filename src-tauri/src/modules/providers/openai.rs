@@ -0,0 +1,706 @@
+use super::http::{describe_request_error, error_from_response};
+use super::messages::{normalize_messages, Content, Message, Role};
+use super::provider::Provider;
+use super::sse::sse_events;
+use super::types::{
+    parse_tool_arguments, ChatEvent, ChatRequest, ChatStream, ModelCapabilities, ModelInfo,
+    ToolCall, ToolDefinition, Usage,
+};
+use crate::modules::error::{AppError, AppResult, ErrorKind};
+use async_trait::async_trait;
+use futures::StreamExt;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+pub const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// A chat completions client for OpenAI or any compatible server
+/// (vLLM, LM Studio, llama.cpp server, LocalAI, Groq, OpenRouter, DeepSeek, ...).
+#[derive(Clone)]
+pub struct OpenAiProvider {
+    kind: String,
+    base_url: String,
+    api_key: Option<String>,
+    key_required: bool,
+    headers: HeaderMap,
+    http: reqwest::Client,
+}
+
+#[derive(Serialize)]
+struct CompletionRequest<'a> {
+    model: &'a str,
+    messages: Vec<Value>,
+    stream: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<Value>,
+    // Ollama-only options (top_k, num_ctx, repeat_penalty) have no equivalent here
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_completion_tokens: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<&'a [String]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelsResponse {
+    #[serde(default)]
+    data: Vec<Value>,
+}
+
+/// A tool call assembled from streamed fragments.
+#[derive(Default)]
+struct PartialToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+impl OpenAiProvider {
+    pub fn new(kind: &str, base_url: &str, api_key: Option<&str>) -> AppResult<Self> {
+        let base_url = base_url.trim().trim_end_matches('/');
+        reqwest::Url::parse(base_url).map_err(|e| {
+            AppError::invalid_input(format!("Invalid {} URL '{}': {}", kind, base_url, e))
+                .with_provider(kind)
+        })?;
+        Ok(Self {
+            kind: kind.to_string(),
+            base_url: base_url.to_string(),
+            api_key: api_key
+                .filter(|api_key| !api_key.trim().is_empty())
+                .map(str::to_string),
+            key_required: true,
+            headers: HeaderMap::new(),
+            http: reqwest::Client::new(),
+        })
+    }
+
+    /// Treat the provider as configured without an API key (e.g. a local server).
+    pub fn without_required_key(mut self) -> Self {
+        self.key_required = false;
+        self
+    }
+
+    /// Send extra headers with every request (e.g. gateway auth or routing headers).
+    pub fn with_headers(mut self, headers: &HashMap<String, String>) -> AppResult<Self> {
+        for (name, value) in headers {
+            let name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|e| {
+                AppError::invalid_input(format!(
+                    "Invalid {} header name '{}': {}",
+                    self.kind, name, e
+                ))
+                .with_provider(&self.kind)
+            })?;
+            let value = HeaderValue::from_str(value.trim()).map_err(|e| {
+                AppError::invalid_input(format!(
+                    "Invalid {} header value for '{}': {}",
+                    self.kind, name, e
+                ))
+                .with_provider(&self.kind)
+            })?;
+            self.headers.insert(name, value);
+        }
+        Ok(self)
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .http
+            .request(method, format!("{}/{}", self.base_url, path))
+            .headers(self.headers.clone());
+        match &self.api_key {
+            Some(api_key) => request.bearer_auth(api_key),
+            None => request,
+        }
+    }
+
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        action: &str,
+    ) -> AppResult<reqwest::Response> {
+        let response = self.send_unchecked(request, action).await?;
+        if !response.status().is_success() {
+            return Err(error_from_response(response, &self.kind, action).await);
+        }
+        Ok(response)
+    }
+
+    /// Send a request without turning an error status into an error.
+    async fn send_unchecked(
+        &self,
+        request: reqwest::RequestBuilder,
+        action: &str,
+    ) -> AppResult<reqwest::Response> {
+        request
+            .send()
+            .await
+            .map_err(|e| describe_request_error(&e, &self.kind, &self.base_url, action))
+    }
+
+    /// Whether this talks to OpenAI itself rather than a compatible server.
+    fn is_openai_api(&self) -> bool {
+        reqwest::Url::parse(&self.base_url)
+            .is_ok_and(|url| url.host_str() == Some("api.openai.com"))
+    }
+
+    async fn fetch_models(&self) -> AppResult<Vec<Value>> {
+        let action = format!("Failed to list {} models", self.kind);
+        let response = self
+            .send(self.request(reqwest::Method::GET, "models"), &action)
+            .await?;
+        let models: ModelsResponse = response.json().await.map_err(|e| {
+            AppError::provider(
+                ErrorKind::Provider,
+                &self.kind,
+                format!("{}: {}", action, e),
+            )
+        })?;
+        Ok(models.data)
+    }
+}
+
+#[async_trait]
+impl Provider for OpenAiProvider {
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn is_configured(&self) -> bool {
+        self.api_key.is_some() || !self.key_required
+    }
+
+    async fn list_models(&self) -> AppResult<Vec<String>> {
+        Ok(self
+            .list_model_info()
+            .await?
+            .into_iter()
+            .map(|info| info.name)
+            .collect())
+    }
+
+    async fn list_model_info(&self) -> AppResult<Vec<ModelInfo>> {
+        let mut models: Vec<ModelInfo> = self
+            .fetch_models()
+            .await?
+            .iter()
+            .filter(|model| model["id"].as_str().is_some_and(is_chat_model))
+            .map(openai_model_info)
+            .collect();
+        if models.is_empty() {
+            return Err(AppError::provider(
+                ErrorKind::NotFound,
+                &self.kind,
+                format!("No models found at {}", self.base_url),
+            ));
+        }
+        models.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(models)
+    }
+
+    async fn stream_chat(&self, request: ChatRequest) -> AppResult<ChatStream> {
+        let messages = normalize_messages(&request.messages)?;
+        if messages.is_empty() {
+            return Err(AppError::invalid_input(format!(
+                "No messages to send to {}",
+                self.kind
+            )));
+        }
+
+        let options = &request.options;
+        let max_tokens = options.num_predict.filter(|n| *n > 0);
+        // OpenAI's reasoning models reject max_tokens, but compatible servers mostly only know it
+        let (max_tokens, max_completion_tokens) = if self.is_openai_api() {
+            (None, max_tokens)
+        } else {
+            (max_tokens, None)
+        };
+        let mut body = CompletionRequest {
+            model: &request.model,
+            messages: openai_messages(&messages),
+            stream: true,
+            tools: request.tools.iter().map(openai_tool).collect(),
+            temperature: options.temperature,
+            top_p: options.top_p,
+            max_tokens,
+            max_completion_tokens,
+            seed: options.seed,
+            stop: options.stop.as_deref().filter(|stop| !stop.is_empty()),
+            stream_options: Some(json!({ "include_usage": true })),
+        };
+        let action = format!("Failed to start {} stream", self.kind);
+        let completions = |body: &CompletionRequest| {
+            self.request(reqwest::Method::POST, "chat/completions")
+                .json(body)
+        };
+        let mut response = self.send_unchecked(completions(&body), &action).await?;
+        // Older compatible servers reject stream_options; they get no usage reported
+        if matches!(response.status().as_u16(), 400 | 422) {
+            let error = error_from_response(response, &self.kind, &action).await;
+            if !error.message.contains("stream_options") {
+                return Err(error);
+            }
+            body.stream_options = None;
+            response = self.send_unchecked(completions(&body), &action).await?;
+        }
+        if !response.status().is_success() {
+            return Err(error_from_response(response, &self.kind, &action).await);
+        }
+
+        let (tx, rx) = mpsc::channel::<AppResult<ChatEvent>>(100);
+        let mut events = sse_events(response);
+        let kind = self.kind.clone();
+
+        tokio::spawn(async move {
+            let mut tool_calls: BTreeMap<u64, PartialToolCall> = BTreeMap::new();
+            let mut usage = None;
+            loop {
+                // Stop reading as soon as the consumer goes away (e.g. cancelled)
+                let event = tokio::select! {
+                    event = events.next() => event,
+                    _ = tx.closed() => return,
+                };
+                let event = match event {
+                    Some(Ok(event)) => event,
+                    Some(Err(e)) => {
+                        let _ = tx.send(Err(e.with_provider(&kind))).await;
+                        return;
+                    }
+                    None => break,
+                };
+                if event.data == "[DONE]" {
+                    break;
+                }
+
+                let chunk: Value = match serde_json::from_str(&event.data) {
+                    Ok(chunk) => chunk,
+                    Err(_) => continue, // Keep-alives and vendor extensions
+                };
+                if let Some(error) = chunk.get("error") {
+                    let message = error["message"].as_str().unwrap_or("unknown error");
+                    let _ = tx
+                        .send(Err(AppError::provider(ErrorKind::Provider, &kind, message)))
+                        .await;
+                    return;
+                }
+
+                // With include_usage the last chunk has the totals and no choices
+                let reported = &chunk["usage"];
+                if reported.is_object() {
+                    usage = Some(Usage {
+                        input_tokens: reported["prompt_tokens"].as_u64().unwrap_or_default(),
+                        output_tokens: reported["completion_tokens"].as_u64().unwrap_or_default(),
+                    });
+                }
+
+                let delta = &chunk["choices"][0]["delta"];
+                if let Some(text) = delta["content"].as_str().filter(|t| !t.is_empty()) {
+                    if tx
+                        .send(Ok(ChatEvent::Text(text.to_string())))
+                        .await
+                        .is_err()
+                    {
+                        return; // Receiver was dropped
+                    }
+                }
+                // Tool calls arrive in fragments keyed by index; the arguments are a JSON
+                // string split across chunks
+                for fragment in delta["tool_calls"].as_array().into_iter().flatten() {
+                    let index = fragment["index"].as_u64().unwrap_or_default();
+                    let call = tool_calls.entry(index).or_default();
+                    if let Some(id) = fragment["id"].as_str() {
+                        call.id = id.to_string();
+                    }
+                    if let Some(name) = fragment["function"]["name"].as_str() {
+                        call.name.push_str(name);
+                    }
+                    if let Some(arguments) = fragment["function"]["arguments"].as_str() {
+                        call.arguments.push_str(arguments);
+                    }
+                }
+            }
+
+            for call in tool_calls.into_values() {
+                let tool_call = ToolCall {
+                    id: if call.id.is_empty() {
+                        format!("call_{}", uuid::Uuid::new_v4().simple())
+                    } else {
+                        call.id
+                    },
+                    name: call.name,
+                    arguments: parse_tool_arguments(&call.arguments),
+                };
+                if tx.send(Ok(ChatEvent::ToolCall(tool_call))).await.is_err() {
+                    return;
+                }
+            }
+            if let Some(usage) = usage {
+                let _ = tx.send(Ok(ChatEvent::Usage(usage))).await;
+            }
+        });
+
+        Ok(Box::pin(ReceiverStream::new(rx)))
+    }
+
+    async fn model_info(&self, model: &str) -> AppResult<ModelInfo> {
+        // Not every compatible server implements /models/{id}, so look it up in the list
+        let info = self
+            .fetch_models()
+            .await?
+            .into_iter()
+            .find(|info| info["id"].as_str() == Some(model))
+            .ok_or_else(|| {
+                AppError::provider(
+                    ErrorKind::NotFound,
+                    &self.kind,
+                    format!("Model {} not found at {}", model, self.base_url),
+                )
+            })?;
+        Ok(openai_model_info(&info))
+    }
+
+    fn health_request(&self) -> AppResult<reqwest::RequestBuilder> {
+        Ok(self.request(reqwest::Method::GET, "models"))
+    }
+}
+
+/// Model lists also include embedding, audio and image models that cannot chat.
+fn is_chat_model(id: &str) -> bool {
+    const NON_CHAT: &[&str] = &[
+        "embed",
+        "whisper",
+        "tts",
+        "dall-e",
+        "moderation",
+        "transcribe",
+        "davinci",
+        "babbage",
+        "image",
+    ];
+    !NON_CHAT.iter().any(|marker| id.contains(marker))
+}
+
+/// Read what a `/models` entry says about the model. OpenAI itself only reports the id;
+/// OpenRouter adds limits, modalities, supported parameters and prices, and vLLM
+/// `max_model_len`.
+fn openai_model_info(info: &Value) -> ModelInfo {
+    let supports = |parameter: &str| {
+        info["supported_parameters"]
+            .as_array()
+            .map(|parameters| parameters.iter().any(|p| p == parameter))
+    };
+    // Prices are USD per token, as strings
+    let per_million = |price: &Value| {
+        price
+            .as_str()
+            .and_then(|price| price.parse::<f64>().ok())
+            .map(|price| price * 1_000_000.0)
+    };
+    ModelInfo {
+        name: info["id"].as_str().unwrap_or_default().to_string(),
+        capabilities: ModelCapabilities {
+            context_length: info["context_length"]
+                .as_u64()
+                .or_else(|| info["max_model_len"].as_u64()),
+            max_output_tokens: info["top_provider"]["max_completion_tokens"].as_u64(),
+            vision: info["architecture"]["input_modalities"]
+                .as_array()
+                .map(|modalities| modalities.iter().any(|m| m == "image")),
+            tools: supports("tools"),
+            json_mode: supports("response_format"),
+            reasoning: supports("reasoning"),
+            input_price: per_million(&info["pricing"]["prompt"]),
+            output_price: per_million(&info["pricing"]["completion"]),
+        },
+    }
+}
+
+fn openai_tool(tool: &ToolDefinition) -> Value {
+    json!({
+        "type": "function",
+        "function": {
+            "name": tool.name,
+            "description": tool.description,
+            "parameters": tool.parameters,
+        }
+    })
+}
+
+fn openai_messages(messages: &[Message]) -> Vec<Value> {
+    let mut out = Vec::new();
+    for message in messages {
+        match message.role {
+            Role::System => out.push(json!({ "role": "system", "content": message.text() })),
+            Role::User => {
+                let has_images = message
+                    .content
+                    .iter()
+                    .any(|content| matches!(content, Content::Image(_)));
+                if !has_images {
+                    out.push(json!({ "role": "user", "content": message.text() }));
+                    continue;
+                }
+                let parts: Vec<Value> = message
+                    .content
+                    .iter()
+                    .filter_map(|content| match content {
+                        Content::Text(text) => Some(json!({ "type": "text", "text": text })),
+                        Content::Image(image) => Some(json!({
+                            "type": "image_url",
+                            "image_url": { "url": image.to_url() }
+                        })),
+                        _ => None,
+                    })
+                    .collect();
+                out.push(json!({ "role": "user", "content": parts }));
+            }
+            Role::Assistant => {
+                let tool_calls: Vec<Value> = message
+                    .content
+                    .iter()
+                    .filter_map(|content| match content {
+                        Content::ToolCall(call) => Some(json!({
+                            "id": call.id,
+                            "type": "function",
+                            "function": {
+                                "name": call.name,
+                                "arguments": call.arguments.to_string(),
+                            }
+                        })),
+                        _ => None,
+                    })
+                    .collect();
+                let text = message.text();
+                let mut assistant = json!({
+                    "role": "assistant",
+                    "content": if text.is_empty() && !tool_calls.is_empty() {
+                        Value::Null
+                    } else {
+                        Value::String(text)
+                    },
+                });
+                if !tool_calls.is_empty() {
+                    assistant["tool_calls"] = Value::Array(tool_calls);
+                }
+                out.push(assistant);
+            }
+            Role::Tool => {
+                for content in &message.content {
+                    if let Content::ToolResult {
+                        call_id, output, ..
+                    } = content
+                    {
+                        out.push(json!({
+                            "role": "tool",
+                            "tool_call_id": call_id,
+                            "content": output,
+                        }));
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::providers::test_server::{Reply, TestServer};
+
+    fn chat_request() -> ChatRequest {
+        ChatRequest {
+            model: "gpt-test".to_string(),
+            messages: vec![json!({ "role": "user", "content": "Hi" })],
+            ..Default::default()
+        }
+    }
+
+    async fn stream(provider: &OpenAiProvider) -> Vec<AppResult<ChatEvent>> {
+        provider
+            .stream_chat(chat_request())
+            .await
+            .unwrap()
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn streams_text_and_usage() {
+        let server = TestServer::start(vec![Reply::sse(&[
+            r#"{"choices":[{"delta":{"role":"assistant","content":"Hel"}}]}"#,
+            r#"{"choices":[{"delta":{"content":"lo"}}]}"#,
+            r#"{"choices":[],"usage":{"prompt_tokens":12,"completion_tokens":3}}"#,
+            "[DONE]",
+        ])]);
+        let provider = OpenAiProvider::new("OpenAI", &server.url, Some("sk-test")).unwrap();
+        let events = stream(&provider).await;
+
+        let request = server.received();
+        assert_eq!(request.path, "/v1/chat/completions");
+        assert_eq!(request.header("authorization"), Some("Bearer sk-test"));
+        assert_eq!(request.body["model"], "gpt-test");
+        assert_eq!(request.body["stream_options"]["include_usage"], true);
+        assert_eq!(
+            request.body["messages"],
+            json!([{ "role": "user", "content": "Hi" }])
+        );
+
+        let events: Vec<ChatEvent> = events.into_iter().map(Result::unwrap).collect();
+        assert!(matches!(&events[0], ChatEvent::Text(text) if text == "Hel"));
+        assert!(matches!(&events[1], ChatEvent::Text(text) if text == "lo"));
+        assert!(matches!(
+            events[2],
+            ChatEvent::Usage(Usage {
+                input_tokens: 12,
+                output_tokens: 3
+            })
+        ));
+        assert_eq!(events.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn assembles_tool_calls_from_fragments() {
+        let server = TestServer::start(vec![Reply::sse(&[
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_1","function":{"name":"get_weather","arguments":"{\"city\":"}}]}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"Oslo\"}"}}]}}]}"#,
+            "[DONE]",
+        ])]);
+        let provider = OpenAiProvider::new("OpenAI", &server.url, Some("sk-test")).unwrap();
+        let events = stream(&provider).await;
+
+        assert_eq!(events.len(), 1);
+        match events[0].as_ref().unwrap() {
+            ChatEvent::ToolCall(call) => {
+                assert_eq!(call.id, "call_1");
+                assert_eq!(call.name, "get_weather");
+                assert_eq!(call.arguments, json!({ "city": "Oslo" }));
+            }
+            event => panic!("expected a tool call, got {:?}", event),
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_without_stream_options_when_rejected() {
+        let server = TestServer::start(vec![
+            Reply::json(
+                400,
+                json!({ "error": { "message": "Unrecognized request argument: stream_options" } }),
+            ),
+            Reply::sse(&[r#"{"choices":[{"delta":{"content":"Hi"}}]}"#, "[DONE]"]),
+        ]);
+        let provider = OpenAiProvider::new("vLLM", &server.url, None)
+            .unwrap()
+            .without_required_key();
+        let events = stream(&provider).await;
+
+        assert!(server.received().body.get("stream_options").is_some());
+        let retry = server.received();
+        assert!(retry.body.get("stream_options").is_none());
+        assert_eq!(retry.header("authorization"), None);
+        assert!(matches!(&events[..], [Ok(ChatEvent::Text(text))] if text == "Hi"));
+    }
+
+    #[tokio::test]
+    async fn reports_other_bad_requests_without_retrying() {
+        let server = TestServer::start(vec![Reply::json(
+            400,
+            json!({ "error": { "message": "temperature must be at most 2" } }),
+        )]);
+        let provider = OpenAiProvider::new("OpenAI", &server.url, Some("sk-test")).unwrap();
+
+        let error = provider.stream_chat(chat_request()).await.err().unwrap();
+        assert_eq!(error.kind, ErrorKind::Provider);
+        assert!(error.message.contains("temperature must be at most 2"));
+        assert!(server.received().body.get("stream_options").is_some());
+    }
+
+    #[tokio::test]
+    async fn sends_max_tokens_to_compatible_servers() {
+        let server = TestServer::start(vec![Reply::sse(&["[DONE]"])]);
+        let provider = OpenAiProvider::new("vLLM", &server.url, None)
+            .unwrap()
+            .without_required_key();
+        let mut request = chat_request();
+        request.options.num_predict = Some(256);
+        let _: Vec<_> = provider.stream_chat(request).await.unwrap().collect().await;
+
+        let body = server.received().body;
+        assert_eq!(body["max_tokens"], 256);
+        assert!(body.get("max_completion_tokens").is_none());
+    }
+
+    #[test]
+    fn recognizes_the_openai_api() {
+        let openai = OpenAiProvider::new("OpenAI", "https://api.openai.com/v1", None).unwrap();
+        let groq = OpenAiProvider::new("Groq", "https://api.groq.com/openai/v1", None).unwrap();
+
+        assert!(openai.is_openai_api());
+        assert!(!groq.is_openai_api());
+    }
+
+    #[tokio::test]
+    async fn reports_error_statuses_and_chunks() {
+        let server = TestServer::start(vec![
+            Reply::json(401, json!({ "error": { "message": "Incorrect API key" } })),
+            Reply::sse(&[r#"{"error":{"message":"model overloaded"}}"#]),
+        ]);
+        let provider = OpenAiProvider::new("OpenAI", &server.url, Some("sk-bad")).unwrap();
+
+        let error = provider.stream_chat(chat_request()).await.err().unwrap();
+        assert_eq!(error.kind, ErrorKind::Auth);
+        assert!(error.message.contains("Incorrect API key"));
+
+        let events = stream(&provider).await;
+        let error = events[0].as_ref().unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Provider);
+        assert_eq!(error.message, "model overloaded");
+    }
+
+    #[tokio::test]
+    async fn lists_chat_models_with_reported_capabilities() {
+        let server = TestServer::start(vec![Reply::json(
+            200,
+            json!({ "data": [
+                { "id": "text-embedding-3-small" },
+                {
+                    "id": "openai/gpt-4o",
+                    "context_length": 128000,
+                    "architecture": { "input_modalities": ["text", "image"] },
+                    "supported_parameters": ["tools"],
+                    "pricing": { "prompt": "0.0000025", "completion": "0.00001" }
+                },
+                { "id": "llama-3-8b", "max_model_len": 8192 }
+            ] }),
+        )]);
+        let provider = OpenAiProvider::new("OpenRouter", &server.url, Some("sk-test")).unwrap();
+        let models = provider.list_model_info().await.unwrap();
+
+        assert_eq!(server.received().path, "/v1/models");
+        let names: Vec<&str> = models.iter().map(|info| info.name.as_str()).collect();
+        assert_eq!(names, ["llama-3-8b", "openai/gpt-4o"]);
+        assert_eq!(models[0].capabilities.context_length, Some(8192));
+        let gpt = &models[1].capabilities;
+        assert_eq!(gpt.context_length, Some(128000));
+        assert_eq!(gpt.vision, Some(true));
+        assert_eq!(gpt.tools, Some(true));
+        assert_eq!(gpt.json_mode, Some(false));
+        assert_eq!(gpt.input_price, Some(2.5));
+    }
+}
@@ -0,0 +1,86 @@
+use ollama_rs::models::ModelOptions;
+use serde::{Deserialize, Serialize};
+
+/// Sampling and length controls for a single generation.
+///
+/// Every field is optional; unset fields fall back to the next layer
+/// (request → conversation → per-model default → the model's own Modelfile).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationOptions {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub top_k: Option<u32>,
+    pub num_ctx: Option<u64>,     // Context window in tokens
+    pub num_predict: Option<i32>, // Max tokens to generate, -1 for unlimited
+    pub repeat_penalty: Option<f32>,
+    pub seed: Option<i32>,
+    pub stop: Option<Vec<String>>,
+    pub thinking_budget: Option<u32>, // Extended thinking tokens (Anthropic)
+}
+
+impl GenerationOptions {
+    /// Overlay `other` on top of `self`; fields set in `other` win.
+    pub fn merged_with(mut self, other: &GenerationOptions) -> Self {
+        if other.temperature.is_some() {
+            self.temperature = other.temperature;
+        }
+        if other.top_p.is_some() {
+            self.top_p = other.top_p;
+        }
+        if other.top_k.is_some() {
+            self.top_k = other.top_k;
+        }
+        if other.num_ctx.is_some() {
+            self.num_ctx = other.num_ctx;
+        }
+        if other.num_predict.is_some() {
+            self.num_predict = other.num_predict;
+        }
+        if other.repeat_penalty.is_some() {
+            self.repeat_penalty = other.repeat_penalty;
+        }
+        if other.seed.is_some() {
+            self.seed = other.seed;
+        }
+        if other.stop.is_some() {
+            self.stop = other.stop.clone();
+        }
+        if other.thinking_budget.is_some() {
+            self.thinking_budget = other.thinking_budget;
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == GenerationOptions::default()
+    }
+
+    pub fn to_ollama(&self) -> ModelOptions {
+        let mut options = ModelOptions::default();
+        if let Some(temperature) = self.temperature {
+            options = options.temperature(temperature);
+        }
+        if let Some(top_p) = self.top_p {
+            options = options.top_p(top_p);
+        }
+        if let Some(top_k) = self.top_k {
+            options = options.top_k(top_k);
+        }
+        if let Some(num_ctx) = self.num_ctx {
+            options = options.num_ctx(num_ctx);
+        }
+        if let Some(num_predict) = self.num_predict {
+            options = options.num_predict(num_predict);
+        }
+        if let Some(repeat_penalty) = self.repeat_penalty {
+            options = options.repeat_penalty(repeat_penalty);
+        }
+        if let Some(seed) = self.seed {
+            options = options.seed(seed);
+        }
+        if let Some(stop) = &self.stop {
+            options = options.stop(stop.clone());
+        }
+        options
+    }
+}
@@ -0,0 +1,52 @@
+use super::health::{check_health, ProviderHealth};
+use super::types::{ChatRequest, ChatStream, ModelCapabilities, ModelInfo};
+use crate::modules::error::AppResult;
+use async_trait::async_trait;
+
+/// A chat backend. Implementations are built from `AppSettings` by the `ProviderRegistry`.
+#[async_trait]
+pub trait Provider: Send + Sync {
+    /// Adapter kind used by the frontend, e.g. "Ollama" or "OpenAI".
+    fn kind(&self) -> &str;
+
+    /// The endpoint requests are sent to.
+    fn base_url(&self) -> &str;
+
+    /// Whether the provider has what it needs to make requests (e.g. an API key).
+    fn is_configured(&self) -> bool;
+
+    async fn list_models(&self) -> AppResult<Vec<String>>;
+
+    /// Models with whatever capabilities the provider reports while listing them.
+    /// Defaults to the bare model list.
+    async fn list_model_info(&self) -> AppResult<Vec<ModelInfo>> {
+        Ok(self
+            .list_models()
+            .await?
+            .into_iter()
+            .map(ModelInfo::named)
+            .collect())
+    }
+
+    async fn stream_chat(&self, request: ChatRequest) -> AppResult<ChatStream>;
+
+    async fn model_info(&self, model: &str) -> AppResult<ModelInfo>;
+
+    async fn capabilities(&self, model: &str) -> AppResult<ModelCapabilities> {
+        Ok(self.model_info(model).await?.capabilities)
+    }
+
+    /// A cheap authenticated request that only succeeds when the backend is reachable
+    /// and accepts our credentials.
+    fn health_request(&self) -> AppResult<reqwest::RequestBuilder>;
+
+    async fn health(&self) -> ProviderHealth {
+        check_health(
+            self.kind(),
+            self.base_url(),
+            self.is_configured(),
+            self.health_request(),
+        )
+        .await
+    }
+}
@@ -0,0 +1,256 @@
+use super::anthropic::AnthropicProvider;
+use super::gemini::GeminiProvider;
+use super::ollama::OllamaProvider;
+use super::openai::{OpenAiProvider, DEFAULT_OPENAI_BASE_URL};
+use super::provider::Provider;
+use crate::modules::error::{AppError, AppResult};
+use crate::modules::settings::{AppSettings, CustomProvider};
+use std::sync::Arc;
+
+pub struct ProviderEntry {
+    pub provider: Arc<dyn Provider>,
+    pub models: Option<Vec<String>>, // Models picked in settings, listed instead of live ones
+}
+
+/// Every provider the app knows about, in the order models are listed.
+pub struct ProviderRegistry {
+    entries: Vec<ProviderEntry>,
+    skipped: Vec<AppError>, // Why providers were left out
+}
+
+impl ProviderRegistry {
+    /// Build every provider from settings. One with an unusable configuration, such as
+    /// a malformed base URL, is left out and reported in [`Self::skipped`] rather than
+    /// taking every other provider down.
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        let openai_key = settings
+            .openai_api_key
+            .as_deref()
+            .or(settings.api_key.as_deref());
+
+        let mut registry = Self {
+            entries: Vec::new(),
+            skipped: Vec::new(),
+        };
+        // Any OpenAI-compatible server can stand in via the base URL; local ones need no key
+        let openai = OpenAiProvider::new(
+            "OpenAI",
+            base_url(&settings.openai_base_url).unwrap_or(DEFAULT_OPENAI_BASE_URL),
+            openai_key,
+        )
+        .map(|provider| {
+            if base_url(&settings.openai_base_url).is_some() {
+                provider.without_required_key()
+            } else {
+                provider
+            }
+        });
+        registry.add(openai, settings.openai_models.clone());
+        registry.add(
+            AnthropicProvider::new(
+                base_url(&settings.anthropic_base_url),
+                settings.anthropic_api_key.as_deref(),
+            ),
+            settings.anthropic_models.clone(),
+        );
+        registry.add(
+            GeminiProvider::new(
+                base_url(&settings.gemini_base_url),
+                settings.gemini_api_key.as_deref(),
+            ),
+            settings.gemini_models.clone(),
+        );
+        registry.add(
+            OpenAiProvider::new(
+                "Groq",
+                base_url(&settings.groq_base_url).unwrap_or("https://api.groq.com/openai/v1"),
+                settings.groq_api_key.as_deref(),
+            ),
+            settings.groq_models.clone(),
+        );
+        registry.add(
+            OpenAiProvider::new(
+                "OpenRouter",
+                base_url(&settings.openrouter_base_url).unwrap_or("https://openrouter.ai/api/v1"),
+                settings.openrouter_api_key.as_deref(),
+            ),
+            settings.openrouter_models.clone(),
+        );
+        registry.add(
+            OpenAiProvider::new(
+                "DeepSeek",
+                base_url(&settings.deepseek_base_url).unwrap_or("https://api.deepseek.com/v1"),
+                settings.deepseek_api_key.as_deref(),
+            ),
+            settings.deepseek_models.clone(),
+        );
+        registry.add(
+            OpenAiProvider::new(
+                "Mistral",
+                base_url(&settings.mistral_base_url).unwrap_or("https://api.mistral.ai/v1"),
+                settings.mistral_api_key.as_deref(),
+            ),
+            settings.mistral_models.clone(),
+        );
+        registry.add(OllamaProvider::from_settings(settings), None);
+        registry.add(
+            OpenAiProvider::new(
+                "X.AI",
+                base_url(&settings.xai_base_url).unwrap_or("https://api.x.ai/v1"),
+                settings.xai_api_key.as_deref(),
+            ),
+            settings.xai_models.clone(),
+        );
+        for custom in settings.custom_providers.iter().flatten() {
+            match registry.custom_provider(custom) {
+                Ok((provider, models)) => registry.register(provider, models),
+                Err(error) => registry.skip(error),
+            }
+        }
+        registry
+    }
+
+    fn custom_provider(
+        &self,
+        custom: &CustomProvider,
+    ) -> AppResult<(OpenAiProvider, Option<Vec<String>>)> {
+        let name = custom.name.trim();
+        if name.is_empty() {
+            return Err(AppError::invalid_input("Custom providers need a name"));
+        }
+        // Models are looked up by kind, so names must not shadow another provider
+        if self
+            .entries
+            .iter()
+            .any(|entry| entry.provider.kind().eq_ignore_ascii_case(name))
+        {
+            return Err(AppError::invalid_input(format!(
+                "Provider name '{}' is already in use",
+                name
+            ))
+            .with_provider(name));
+        }
+        let mut provider = OpenAiProvider::new(name, &custom.base_url, custom.api_key.as_deref())
+            .map_err(|e| e.with_provider(name))?
+            .without_required_key();
+        if let Some(headers) = &custom.headers {
+            provider = provider
+                .with_headers(headers)
+                .map_err(|e| e.with_provider(name))?;
+        }
+        // An empty list falls back to discovery as well
+        let models = custom.models.clone().filter(|models| !models.is_empty());
+        Ok((provider, models))
+    }
+
+    fn add(&mut self, provider: AppResult<impl Provider + 'static>, models: Option<Vec<String>>) {
+        match provider {
+            Ok(provider) => self.register(provider, models),
+            Err(error) => self.skip(error),
+        }
+    }
+
+    fn skip(&mut self, error: AppError) {
+        log::warn!(
+            "Skipping provider '{}': {}",
+            error.provider.as_deref().unwrap_or_default(),
+            error
+        );
+        self.skipped.push(error);
+    }
+
+    fn register(&mut self, provider: impl Provider + 'static, models: Option<Vec<String>>) {
+        self.entries.push(ProviderEntry {
+            provider: Arc::new(provider),
+            models,
+        });
+    }
+
+    pub fn entries(&self) -> &[ProviderEntry] {
+        &self.entries
+    }
+
+    /// Errors of the providers that were left out, in listing order. Each names its
+    /// provider in `provider`.
+    pub fn skipped(&self) -> &[AppError] {
+        &self.skipped
+    }
+
+    pub fn get(&self, kind: &str) -> AppResult<Arc<dyn Provider>> {
+        self.entries
+            .iter()
+            .find(|entry| entry.provider.kind() == kind)
+            .map(|entry| entry.provider.clone())
+            .ok_or_else(|| {
+                // A provider left out of the registry is known, just misconfigured
+                self.skipped
+                    .iter()
+                    .find(|error| error.provider.as_deref() == Some(kind))
+                    .cloned()
+                    .unwrap_or_else(|| AppError::not_found(format!("Unknown provider: {}", kind)))
+            })
+    }
+}
+
+/// A base URL from settings; cleared fields are saved as empty strings.
+fn base_url(value: &Option<String>) -> Option<&str> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::error::ErrorKind;
+
+    fn custom(name: &str, base_url: &str) -> CustomProvider {
+        CustomProvider {
+            name: name.to_string(),
+            base_url: base_url.to_string(),
+            api_key: None,
+            headers: None,
+            models: None,
+        }
+    }
+
+    #[test]
+    fn skips_invalid_custom_providers() {
+        let settings = AppSettings {
+            custom_providers: Some(vec![
+                custom("", "http://localhost:8000/v1"),
+                custom("openai", "http://localhost:8000/v1"),
+                custom("Broken", "not a url"),
+                custom("vLLM", "http://localhost:8000/v1"),
+            ]),
+            ..Default::default()
+        };
+        let registry = ProviderRegistry::from_settings(&settings);
+
+        assert!(registry.get("vLLM").is_ok());
+        assert!(registry.get("Broken").is_err());
+        assert_eq!(registry.skipped().len(), 3);
+        assert_eq!(registry.skipped()[2].provider.as_deref(), Some("Broken"));
+    }
+
+    #[test]
+    fn skips_built_in_providers_with_invalid_urls() {
+        let settings = AppSettings {
+            anthropic_base_url: Some("not a url".to_string()),
+            ollama_base_url: Some("http://[::1".to_string()),
+            ..Default::default()
+        };
+        let registry = ProviderRegistry::from_settings(&settings);
+
+        let skipped: Vec<_> = registry
+            .skipped()
+            .iter()
+            .map(|error| error.provider.as_deref())
+            .collect();
+        assert_eq!(skipped, [Some("Anthropic"), Some("Ollama")]);
+        assert!(registry.get("OpenAI").is_ok());
+        let error = registry.get("Anthropic").err().unwrap();
+        assert_eq!(error.kind, ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::modules::error::{AppError, AppResult, ErrorKind};
+use futures::{Stream, StreamExt};
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::pin::Pin;
+
+/// One server-sent event.
+#[derive(Debug, Clone, Default)]
+pub struct SseEvent {
+    pub event: Option<String>,
+    pub data: String,
+}
+
+pub type SseStream = Pin<Box<dyn Stream<Item = AppResult<SseEvent>> + Send>>;
+
+/// Split a streaming response body into server-sent events.
+///
+/// Lines are only decoded once complete, so multi-byte characters split across
+/// chunks survive. Comments and fields other than `event` and `data` are ignored.
+pub fn sse_events(response: reqwest::Response) -> SseStream {
+    parse_sse(response.bytes_stream())
+}
+
+/// Split a stream of body chunks into server-sent events.
+fn parse_sse<S, B, E>(body: S) -> SseStream
+where
+    S: Stream<Item = Result<B, E>> + Send + 'static,
+    B: AsRef<[u8]>,
+    E: Display,
+{
+    struct State<S> {
+        body: Pin<Box<S>>,
+        buffer: Vec<u8>,
+        current: SseEvent,
+        ready: VecDeque<SseEvent>,
+        done: bool,
+    }
+
+    impl<S> State<S> {
+        fn take_lines(&mut self) {
+            while let Some(end) = self.buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                self.line(line.trim_end_matches(['\r', '\n']));
+            }
+        }
+
+        fn line(&mut self, line: &str) {
+            if line.is_empty() {
+                self.dispatch();
+                return;
+            }
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => self.current.event = Some(value.to_string()),
+                "data" => {
+                    if !self.current.data.is_empty() {
+                        self.current.data.push('\n');
+                    }
+                    self.current.data.push_str(value);
+                }
+                _ => {}
+            }
+        }
+
+        fn dispatch(&mut self) {
+            let event = std::mem::take(&mut self.current);
+            if event.event.is_some() || !event.data.is_empty() {
+                self.ready.push_back(event);
+            }
+        }
+    }
+
+    let state = State {
+        body: Box::pin(body),
+        buffer: Vec::new(),
+        current: SseEvent::default(),
+        ready: VecDeque::new(),
+        done: false,
+    };
+
+    Box::pin(futures::stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.ready.pop_front() {
+                return Some((Ok(event), state));
+            }
+            if state.done {
+                return None;
+            }
+            match state.body.next().await {
+                Some(Ok(chunk)) => {
+                    state.buffer.extend_from_slice(chunk.as_ref());
+                    state.take_lines();
+                }
+                Some(Err(e)) => {
+                    state.done = true;
+                    let error = AppError::new(
+                        ErrorKind::Network,
+                        format!("Connection lost while streaming: {}", e),
+                    );
+                    return Some((Err(error), state));
+                }
+                None => {
+                    // Servers may close without a trailing blank line
+                    state.done = true;
+                    let rest =
+                        String::from_utf8_lossy(&std::mem::take(&mut state.buffer)).into_owned();
+                    if !rest.is_empty() {
+                        state.line(rest.trim_end_matches('\r'));
+                    }
+                    state.dispatch();
+                }
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn parse(chunks: &[&[u8]]) -> Vec<AppResult<SseEvent>> {
+        let chunks: Vec<Result<Vec<u8>, String>> =
+            chunks.iter().map(|chunk| Ok(chunk.to_vec())).collect();
+        parse_sse(futures::stream::iter(chunks)).collect().await
+    }
+
+    #[tokio::test]
+    async fn splits_events_across_chunks() {
+        let events = parse(&[
+            b"event: message_start\ndata: {\"a\":",
+            b" 1}\n\n: keep-alive\n\ndata: first\r\ndata: second\r\n\r\n",
+            b"data: [DONE]",
+        ])
+        .await;
+        let events: Vec<SseEvent> = events.into_iter().map(Result::unwrap).collect();
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].event.as_deref(), Some("message_start"));
+        assert_eq!(events[0].data, "{\"a\": 1}");
+        assert_eq!(events[1].event, None);
+        assert_eq!(events[1].data, "first\nsecond");
+        assert_eq!(events[2].data, "[DONE]");
+    }
+
+    #[tokio::test]
+    async fn keeps_characters_split_across_chunks() {
+        let text = "data: héllo\n\n".as_bytes();
+        let split = text.iter().position(|b| *b == 0xc3).unwrap() + 1;
+        let events = parse(&[&text[..split], &text[split..]]).await;
+        assert_eq!(events[0].as_ref().unwrap().data, "héllo");
+    }
+
+    #[tokio::test]
+    async fn reports_a_broken_body_as_a_network_error() {
+        let chunks: Vec<Result<Vec<u8>, String>> = vec![
+            Ok(b"data: partial\n\n".to_vec()),
+            Err("connection reset".to_string()),
+            Ok(b"data: never read\n\n".to_vec()),
+        ];
+        let events: Vec<_> = parse_sse(futures::stream::iter(chunks)).collect().await;
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].as_ref().unwrap().data, "partial");
+        assert_eq!(events[1].as_ref().unwrap_err().kind, ErrorKind::Network);
+    }
+}
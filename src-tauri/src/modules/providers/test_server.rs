@@ -0,0 +1,134 @@
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+
+/// A canned reply of [`TestServer`].
+pub struct Reply {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Reply {
+    /// A `text/event-stream` reply with one `data:` event per item.
+    pub fn sse(events: &[&str]) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/event-stream",
+            body: events
+                .iter()
+                .map(|data| format!("data: {}\n\n", data))
+                .collect(),
+        }
+    }
+
+    /// An `application/x-ndjson` reply with one line per item.
+    pub fn ndjson(lines: &[&str]) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/x-ndjson",
+            body: lines.iter().map(|line| format!("{}\n", line)).collect(),
+        }
+    }
+
+    /// A reply serving a file, such as an image being downloaded.
+    pub fn file(content_type: &'static str, body: &str) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            body: body.to_string(),
+        }
+    }
+
+    pub fn json(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+}
+
+/// A request received by [`TestServer`].
+pub struct Received {
+    pub path: String,
+    pub headers: Vec<(String, String)>, // Names lowercased
+    pub body: Value,
+}
+
+impl Received {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A local HTTP server answering one request per [`Reply`], in order, on a new
+/// connection each.
+pub struct TestServer {
+    pub url: String,
+    received: mpsc::Receiver<Received>,
+}
+
+impl TestServer {
+    pub fn start(replies: Vec<Reply>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1", listener.local_addr().unwrap());
+        let (tx, received) = mpsc::channel();
+        thread::spawn(move || {
+            for reply in replies {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let path = request_line
+                    .split_whitespace()
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_string();
+                let mut headers = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let Some((name, value)) = line.trim_end().split_once(':') else {
+                        break;
+                    };
+                    headers.push((name.to_ascii_lowercase(), value.trim().to_string()));
+                }
+                let length = headers
+                    .iter()
+                    .find(|(name, _)| name == "content-length")
+                    .and_then(|(_, value)| value.parse().ok())
+                    .unwrap_or(0);
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+
+                let _ = tx.send(Received {
+                    path,
+                    headers,
+                    body: serde_json::from_slice(&body).unwrap_or(Value::Null),
+                });
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Test\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    reply.status,
+                    reply.content_type,
+                    reply.body.len(),
+                    reply.body
+                )
+                .unwrap();
+            }
+        });
+        Self { url, received }
+    }
+
+    /// The next request the server answered.
+    pub fn received(&self) -> Received {
+        self.received.recv().unwrap()
+    }
+}
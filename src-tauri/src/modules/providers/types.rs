@@ -0,0 +1,127 @@
+use super::options::GenerationOptions;
+use crate::modules::error::AppResult;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::pin::Pin;
+
+/// A function the model may call, described by a JSON Schema for its arguments.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub parameters: Value,
+}
+
+/// A tool invocation requested by the model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: Value,
+}
+
+/// Token counts reported by the provider for one generation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// One item of a streamed chat response.
+#[derive(Debug, Clone)]
+pub enum ChatEvent {
+    Text(String),
+    Reasoning(String),
+    /// Closes a reasoning block; has to be sent back with it in later turns.
+    ReasoningSignature(String),
+    ToolCall(ToolCall),
+    Usage(Usage),
+}
+
+/// Parse streamed tool call arguments. Models occasionally produce invalid JSON;
+/// it is passed on as a string then.
+pub fn parse_tool_arguments(arguments: &str) -> Value {
+    if arguments.trim().is_empty() {
+        return Value::Object(Default::default());
+    }
+    serde_json::from_str(arguments).unwrap_or_else(|_| Value::String(arguments.to_string()))
+}
+
+/// A provider-neutral chat request; each provider converts the AI SDK messages itself.
+#[derive(Debug, Clone, Default)]
+pub struct ChatRequest {
+    pub model: String,
+    pub messages: Vec<Value>, // AI SDK UI or model messages
+    pub options: GenerationOptions,
+    pub tools: Vec<ToolDefinition>,
+}
+
+pub type ChatStream = Pin<Box<dyn Stream<Item = AppResult<ChatEvent>> + Send>>;
+
+/// What a model supports and costs. `None` means unknown, so records from different
+/// sources can be layered with `merged_with`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelCapabilities {
+    pub context_length: Option<u64>,
+    pub max_output_tokens: Option<u64>,
+    pub vision: Option<bool>,
+    pub tools: Option<bool>,
+    pub json_mode: Option<bool>,
+    pub reasoning: Option<bool>,
+    pub input_price: Option<f64>, // USD per million tokens
+    pub output_price: Option<f64>,
+}
+
+impl ModelCapabilities {
+    /// Overlay `other` on top of `self`; fields set in `other` win.
+    pub fn merged_with(mut self, other: &ModelCapabilities) -> Self {
+        if other.context_length.is_some() {
+            self.context_length = other.context_length;
+        }
+        if other.max_output_tokens.is_some() {
+            self.max_output_tokens = other.max_output_tokens;
+        }
+        if other.vision.is_some() {
+            self.vision = other.vision;
+        }
+        if other.tools.is_some() {
+            self.tools = other.tools;
+        }
+        if other.json_mode.is_some() {
+            self.json_mode = other.json_mode;
+        }
+        if other.reasoning.is_some() {
+            self.reasoning = other.reasoning;
+        }
+        if other.input_price.is_some() {
+            self.input_price = other.input_price;
+        }
+        if other.output_price.is_some() {
+            self.output_price = other.output_price;
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == ModelCapabilities::default()
+    }
+}
+
+/// Provider-neutral model metadata.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub name: String,
+    pub capabilities: ModelCapabilities,
+}
+
+impl ModelInfo {
+    /// A model known only by name.
+    pub fn named(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            capabilities: ModelCapabilities::default(),
+        }
+    }
+}
@@ -1,5 +1,7 @@
+use crate::modules::error::{AppError, AppResult, Context};
+use crate::modules::providers::{GenerationOptions, ModelCapabilities, ProviderRegistry};
 use serde::{Deserialize, Serialize};
-use std::{fs, io::Write, path::PathBuf};
+use std::{collections::HashMap, fs, io::Write, path::PathBuf};
 use tauri::Manager;
 
 fn is_openrouter_model(model: &str) -> bool {
@@ -30,6 +32,17 @@ pub struct AppearanceSettings {
     pub destructive_color: Option<String>,
 }
 
+/// A user-defined OpenAI-compatible endpoint, such as an internal gateway or a
+/// self-hosted server. Its models are listed with `name` as the adapter kind.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CustomProvider {
+    pub name: String,
+    pub base_url: String, // e.g. "http://gpu-box:8000/v1"
+    pub api_key: Option<String>,
+    pub headers: Option<HashMap<String, String>>, // Sent with every request
+    pub models: Option<Vec<String>>,              // None discovers models via /models
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AppSettings {
     pub api_key: Option<String>,
@@ -45,18 +58,26 @@ pub struct AppSettings {
     pub groq_api_key: Option<String>,
     pub openrouter_api_key: Option<String>,
     pub deepseek_api_key: Option<String>,
+    pub mistral_api_key: Option<String>,
+    pub xai_api_key: Option<String>,
+    pub ollama_api_key: Option<String>, // Optional bearer token for proxied Ollama servers
     pub openai_base_url: Option<String>,
     pub anthropic_base_url: Option<String>,
     pub gemini_base_url: Option<String>,
     pub groq_base_url: Option<String>,
     pub openrouter_base_url: Option<String>,
     pub deepseek_base_url: Option<String>,
+    pub mistral_base_url: Option<String>,
+    pub xai_base_url: Option<String>,
+    pub ollama_base_url: Option<String>, // e.g. "http://gpu-box:11434"
     pub openai_models: Option<Vec<String>>,
     pub anthropic_models: Option<Vec<String>>,
     pub gemini_models: Option<Vec<String>>,
     pub groq_models: Option<Vec<String>>,
     pub openrouter_models: Option<Vec<String>>,
     pub deepseek_models: Option<Vec<String>>,
+    pub mistral_models: Option<Vec<String>>,
+    pub xai_models: Option<Vec<String>>,
     // Provider toggles
     pub openai_enabled: Option<bool>,
     pub anthropic_enabled: Option<bool>,
@@ -64,6 +85,13 @@ pub struct AppSettings {
     pub groq_enabled: Option<bool>,
     pub openrouter_enabled: Option<bool>,
     pub deepseek_enabled: Option<bool>,
+    pub mistral_enabled: Option<bool>,
+    pub xai_enabled: Option<bool>,
+    pub custom_providers: Option<Vec<CustomProvider>>,
+    // Default generation options per model id
+    pub model_options: Option<HashMap<String, GenerationOptions>>,
+    // Capability overrides per model id prefix, layered over provider and bundled data
+    pub model_capabilities: Option<HashMap<String, ModelCapabilities>>,
     pub appearance: Option<AppearanceSettings>,
 }
 
@@ -73,21 +101,21 @@ pub struct SettingsFileLegacy {
     pub api_url: Option<String>,
 }
 
-pub fn settings_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
-    let mut dir = app.path().app_data_dir().unwrap();
+pub fn settings_path(app: &tauri::AppHandle) -> AppResult<PathBuf> {
+    let mut dir = app.path().app_data_dir().context("paths")?;
     if !dir.exists() {
-        fs::create_dir_all(&dir).map_err(|e| format!("failed to create app config dir: {e}"))?;
+        fs::create_dir_all(&dir).context("failed to create app config dir")?;
     }
     dir.push("settings.json");
     Ok(dir)
 }
 
-pub fn read_settings(app: &tauri::AppHandle) -> Result<AppSettings, String> {
+pub fn read_settings(app: &tauri::AppHandle) -> AppResult<AppSettings> {
     let path = settings_path(app)?;
     if !path.exists() {
         return Ok(AppSettings::default());
     }
-    let data = fs::read(&path).map_err(|e| format!("read settings failed: {e}"))?;
+    let data = fs::read(&path).context("read settings failed")?;
     // Try new format, fallback to legacy
     if let Ok(s) = serde_json::from_slice::<AppSettings>(&data) {
         return Ok(s);
@@ -102,21 +130,80 @@ pub fn read_settings(app: &tauri::AppHandle) -> Result<AppSettings, String> {
     Ok(AppSettings::default())
 }
 
-pub fn write_settings(app: &tauri::AppHandle, settings: &AppSettings) -> Result<(), String> {
+pub fn write_settings(app: &tauri::AppHandle, settings: &AppSettings) -> AppResult<()> {
     let path = settings_path(app)?;
     if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("create parent dir failed: {e}"))?;
+        fs::create_dir_all(parent).context("create parent dir failed")?;
     }
-    let json = serde_json::to_vec_pretty(settings)
-        .map_err(|e| format!("serialize settings failed: {e}"))?;
-    let mut file =
-        fs::File::create(&path).map_err(|e| format!("open settings file failed: {e}"))?;
+    let json = serde_json::to_vec_pretty(settings).context("serialize settings failed")?;
+    let mut file = fs::File::create(&path).context("open settings file failed")?;
     file.write_all(&json)
-        .map_err(|e| format!("write settings file failed: {e}"))?;
+        .context("write settings file failed")?;
     Ok(())
 }
 
-pub fn merge_settings(base: &mut AppSettings, update: AppSettings) {
+/// Stands in for a stored key in settings sent to the webview, which never sees the keys
+/// themselves. Sent back unchanged, it keeps the stored key.
+pub const REDACTED_KEY: &str = "********";
+
+fn api_keys_mut(settings: &mut AppSettings) -> [&mut Option<String>; 11] {
+    [
+        &mut settings.api_key,
+        &mut settings.huggingface_token,
+        &mut settings.openai_api_key,
+        &mut settings.anthropic_api_key,
+        &mut settings.gemini_api_key,
+        &mut settings.groq_api_key,
+        &mut settings.openrouter_api_key,
+        &mut settings.deepseek_api_key,
+        &mut settings.mistral_api_key,
+        &mut settings.xai_api_key,
+        &mut settings.ollama_api_key,
+    ]
+}
+
+fn has_key(key: &Option<String>) -> bool {
+    key.as_deref().is_some_and(|key| !key.trim().is_empty())
+}
+
+fn redact_key(key: &mut Option<String>) {
+    *key = has_key(key).then(|| REDACTED_KEY.to_string());
+}
+
+/// Settings as the webview may see them, with every stored key replaced by
+/// [`REDACTED_KEY`].
+pub fn redact_settings(mut settings: AppSettings) -> AppSettings {
+    for key in api_keys_mut(&mut settings) {
+        redact_key(key);
+    }
+    for provider in settings.custom_providers.iter_mut().flatten() {
+        redact_key(&mut provider.api_key);
+    }
+    settings
+}
+
+pub fn merge_settings(base: &mut AppSettings, mut update: AppSettings) {
+    // A redacted or blank key is the webview echoing settings back, not a new key;
+    // keys are cleared through `set_api_key`
+    for key in api_keys_mut(&mut update) {
+        if !has_key(key) || key.as_deref() == Some(REDACTED_KEY) {
+            *key = None;
+        }
+    }
+    if let Some(providers) = update.custom_providers.as_mut() {
+        let stored = base.custom_providers.as_deref().unwrap_or_default();
+        for (index, provider) in providers.iter_mut().enumerate() {
+            if provider.api_key.as_deref() == Some(REDACTED_KEY) {
+                // Match by name, or by position for a provider that was renamed
+                provider.api_key = stored
+                    .iter()
+                    .find(|p| p.name == provider.name)
+                    .or_else(|| stored.get(index))
+                    .and_then(|p| p.api_key.clone());
+            }
+        }
+    }
+
     if update.api_key.is_some() {
         base.api_key = update.api_key;
     }
@@ -153,6 +240,15 @@ pub fn merge_settings(base: &mut AppSettings, update: AppSettings) {
     if update.deepseek_api_key.is_some() {
         base.deepseek_api_key = update.deepseek_api_key;
     }
+    if update.mistral_api_key.is_some() {
+        base.mistral_api_key = update.mistral_api_key;
+    }
+    if update.xai_api_key.is_some() {
+        base.xai_api_key = update.xai_api_key;
+    }
+    if update.ollama_api_key.is_some() {
+        base.ollama_api_key = update.ollama_api_key;
+    }
     if update.openai_base_url.is_some() {
         base.openai_base_url = update.openai_base_url;
     }
@@ -171,6 +267,15 @@ pub fn merge_settings(base: &mut AppSettings, update: AppSettings) {
     if update.deepseek_base_url.is_some() {
         base.deepseek_base_url = update.deepseek_base_url;
     }
+    if update.mistral_base_url.is_some() {
+        base.mistral_base_url = update.mistral_base_url;
+    }
+    if update.xai_base_url.is_some() {
+        base.xai_base_url = update.xai_base_url;
+    }
+    if update.ollama_base_url.is_some() {
+        base.ollama_base_url = update.ollama_base_url;
+    }
     if update.openai_models.is_some() {
         base.openai_models = update.openai_models;
     }
@@ -189,6 +294,12 @@ pub fn merge_settings(base: &mut AppSettings, update: AppSettings) {
     if update.deepseek_models.is_some() {
         base.deepseek_models = update.deepseek_models;
     }
+    if update.mistral_models.is_some() {
+        base.mistral_models = update.mistral_models;
+    }
+    if update.xai_models.is_some() {
+        base.xai_models = update.xai_models;
+    }
     if update.openai_enabled.is_some() {
         base.openai_enabled = update.openai_enabled;
     }
@@ -207,6 +318,37 @@ pub fn merge_settings(base: &mut AppSettings, update: AppSettings) {
     if update.deepseek_enabled.is_some() {
         base.deepseek_enabled = update.deepseek_enabled;
     }
+    if update.mistral_enabled.is_some() {
+        base.mistral_enabled = update.mistral_enabled;
+    }
+    if update.xai_enabled.is_some() {
+        base.xai_enabled = update.xai_enabled;
+    }
+    if update.custom_providers.is_some() {
+        base.custom_providers = update.custom_providers;
+    }
+    if let Some(up) = update.model_options {
+        // Entries are replaced per model; an empty options object removes the default
+        let current = base.model_options.get_or_insert_with(Default::default);
+        for (model, options) in up {
+            if options.is_empty() {
+                current.remove(&model);
+            } else {
+                current.insert(model, options);
+            }
+        }
+    }
+    if let Some(up) = update.model_capabilities {
+        // Entries are replaced per model; an empty record removes the override
+        let current = base.model_capabilities.get_or_insert_with(Default::default);
+        for (model, capabilities) in up {
+            if capabilities.is_empty() {
+                current.remove(&model);
+            } else {
+                current.insert(model, capabilities);
+            }
+        }
+    }
     if let Some(up) = update.appearance {
         let current = base.appearance.get_or_insert_with(Default::default);
         if up.theme.is_some() {
@@ -293,26 +435,38 @@ pub fn setup_provider_env_for_model(app: &tauri::AppHandle, model: &str) {
 
 // Tauri Commands
 #[tauri::command]
-pub async fn get_settings(app: tauri::AppHandle) -> Result<AppSettings, String> {
-    read_settings(&app)
+pub async fn get_settings(app: tauri::AppHandle) -> AppResult<AppSettings> {
+    read_settings(&app).map(redact_settings)
 }
 
 #[tauri::command]
-pub async fn update_settings(
-    app: tauri::AppHandle,
-    update: AppSettings,
-) -> Result<AppSettings, String> {
+pub async fn update_settings(app: tauri::AppHandle, update: AppSettings) -> AppResult<AppSettings> {
     let mut current = read_settings(&app)?;
+    let skipped_before = ProviderRegistry::from_settings(&current).skipped().to_vec();
     merge_settings(&mut current, update);
+    // Reject an unusable base URL up front rather than saving it to be skipped. Problems
+    // already saved are not this update's fault and must not block unrelated changes.
+    let introduced = ProviderRegistry::from_settings(&current)
+        .skipped()
+        .iter()
+        .find(|error| {
+            !skipped_before
+                .iter()
+                .any(|old| old.provider == error.provider && old.message == error.message)
+        })
+        .cloned();
+    if let Some(error) = introduced {
+        return Err(error);
+    }
     write_settings(&app, &current)?;
-    Ok(current)
+    Ok(redact_settings(current))
 }
 
 #[tauri::command]
-pub async fn reset_settings(app: tauri::AppHandle) -> Result<(), String> {
+pub async fn reset_settings(app: tauri::AppHandle) -> AppResult<()> {
     let path = settings_path(&app)?;
     if path.exists() {
-        fs::remove_file(&path).map_err(|e| format!("remove settings failed: {e}"))?;
+        fs::remove_file(&path).context("remove settings failed")?;
     }
     Ok(())
 }
@@ -325,15 +479,9 @@ pub struct GeneralInfo {
 }
 
 #[tauri::command]
-pub async fn get_general_info(app: tauri::AppHandle) -> Result<GeneralInfo, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("paths: {e}"))?;
-    let logs_dir = app
-        .path()
-        .app_log_dir()
-        .map_err(|e| format!("paths: {e}"))?;
+pub async fn get_general_info(app: tauri::AppHandle) -> AppResult<GeneralInfo> {
+    let app_data_dir = app.path().app_data_dir().context("paths")?;
+    let logs_dir = app.path().app_log_dir().context("paths")?;
     Ok(GeneralInfo {
         app_version: app.package_info().version.to_string(),
         app_data_dir: app_data_dir.to_string_lossy().to_string(),
@@ -342,19 +490,19 @@ pub async fn get_general_info(app: tauri::AppHandle) -> Result<GeneralInfo, Stri
 }
 
 #[tauri::command]
-pub async fn set_data_dir(app: tauri::AppHandle, new_dir: String) -> Result<AppSettings, String> {
+pub async fn set_data_dir(app: tauri::AppHandle, new_dir: String) -> AppResult<AppSettings> {
     let mut current = read_settings(&app)?;
     current.data_dir = Some(new_dir);
     write_settings(&app, &current)?;
-    Ok(current)
+    Ok(redact_settings(current))
 }
 
 #[tauri::command]
-pub async fn reset_appearance(app: tauri::AppHandle) -> Result<AppSettings, String> {
+pub async fn reset_appearance(app: tauri::AppHandle) -> AppResult<AppSettings> {
     let mut current = read_settings(&app)?;
     current.appearance = None;
     write_settings(&app, &current)?;
-    Ok(current)
+    Ok(redact_settings(current))
 }
 
 // Legacy endpoints kept for compatibility with earlier UI
@@ -363,7 +511,7 @@ pub async fn save_settings(
     app: tauri::AppHandle,
     api_key: String,
     api_url: Option<String>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let mut current = read_settings(&app)?;
     current.api_key = Some(api_key);
     current.api_url = api_url;
@@ -371,11 +519,11 @@ pub async fn save_settings(
 }
 
 #[tauri::command]
-pub async fn load_settings(app: tauri::AppHandle) -> Result<Option<SettingsFileLegacy>, String> {
+pub async fn load_settings(app: tauri::AppHandle) -> AppResult<Option<SettingsFileLegacy>> {
     let current = read_settings(&app)?;
-    if let Some(api_key) = current.api_key.clone() {
+    if has_key(&current.api_key) {
         return Ok(Some(SettingsFileLegacy {
-            api_key,
+            api_key: REDACTED_KEY.to_string(),
             api_url: current.api_url.clone(),
         }));
     }
@@ -383,36 +531,18 @@ pub async fn load_settings(app: tauri::AppHandle) -> Result<Option<SettingsFileL
 }
 
 #[tauri::command]
-pub async fn has_settings(app: tauri::AppHandle) -> Result<bool, String> {
+pub async fn has_settings(app: tauri::AppHandle) -> AppResult<bool> {
     let path = settings_path(&app)?;
     Ok(path.exists())
 }
 
 // API Key management commands
-#[tauri::command]
-pub async fn get_api_key(
-    app: tauri::AppHandle,
-    provider: String,
-) -> Result<Option<String>, String> {
-    let settings = read_settings(&app)?;
-    let api_key = match provider.as_str() {
-        "openai" => settings.openai_api_key,
-        "anthropic" => settings.anthropic_api_key,
-        "google" | "gemini" => settings.gemini_api_key,
-        "groq" => settings.groq_api_key,
-        "openrouter" => settings.openrouter_api_key,
-        "deepseek" => settings.deepseek_api_key,
-        _ => None,
-    };
-    Ok(api_key)
-}
-
 #[tauri::command]
 pub async fn set_api_key(
     app: tauri::AppHandle,
     provider: String,
     api_key: String,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let mut settings = read_settings(&app)?;
     match provider.as_str() {
         "openai" => settings.openai_api_key = Some(api_key),
@@ -421,49 +551,123 @@ pub async fn set_api_key(
         "groq" => settings.groq_api_key = Some(api_key),
         "openrouter" => settings.openrouter_api_key = Some(api_key),
         "deepseek" => settings.deepseek_api_key = Some(api_key),
-        _ => return Err(format!("Unknown provider: {}", provider)),
+        "mistral" => settings.mistral_api_key = Some(api_key),
+        "xai" | "x.ai" => settings.xai_api_key = Some(api_key),
+        "ollama" => settings.ollama_api_key = Some(api_key),
+        _ => {
+            return Err(AppError::not_found(format!(
+                "Unknown provider: {}",
+                provider
+            )))
+        }
     }
     write_settings(&app, &settings)
 }
 
+/// What the webview may know about a built-in provider: where it points and whether a
+/// key is stored, but never the key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderConfig {
+    pub base_url: Option<String>,
+    pub has_key: bool,
+}
+
 #[tauri::command]
 pub async fn get_provider_config(
     app: tauri::AppHandle,
     provider: String,
-) -> Result<ProviderConfig, String> {
+) -> AppResult<ProviderConfig> {
     let settings = read_settings(&app)?;
-    let config = match provider.as_str() {
-        "openai" => ProviderConfig {
-            api_key: settings.openai_api_key,
-            base_url: settings.openai_base_url,
-        },
-        "anthropic" => ProviderConfig {
-            api_key: settings.anthropic_api_key,
-            base_url: settings.anthropic_base_url,
-        },
-        "google" | "gemini" => ProviderConfig {
-            api_key: settings.gemini_api_key,
-            base_url: settings.gemini_base_url,
-        },
-        "groq" => ProviderConfig {
-            api_key: settings.groq_api_key,
-            base_url: None, // Groq doesn't have configurable base URL
-        },
-        "openrouter" => ProviderConfig {
-            api_key: settings.openrouter_api_key,
-            base_url: None, // OpenRouter has fixed base URL
-        },
-        "deepseek" => ProviderConfig {
-            api_key: settings.deepseek_api_key,
-            base_url: settings.deepseek_base_url,
-        },
-        _ => return Err(format!("Unknown provider: {}", provider)),
+    let (key, base_url) = match provider.as_str() {
+        "openai" => (settings.openai_api_key, settings.openai_base_url),
+        "anthropic" => (settings.anthropic_api_key, settings.anthropic_base_url),
+        "google" | "gemini" => (settings.gemini_api_key, settings.gemini_base_url),
+        "groq" => (settings.groq_api_key, settings.groq_base_url),
+        "openrouter" => (settings.openrouter_api_key, settings.openrouter_base_url),
+        "deepseek" => (settings.deepseek_api_key, settings.deepseek_base_url),
+        "mistral" => (settings.mistral_api_key, settings.mistral_base_url),
+        "xai" | "x.ai" => (settings.xai_api_key, settings.xai_base_url),
+        "ollama" => (settings.ollama_api_key, settings.ollama_base_url),
+        _ => {
+            return Err(AppError::not_found(format!(
+                "Unknown provider: {}",
+                provider
+            )))
+        }
     };
-    Ok(config)
+    Ok(ProviderConfig {
+        base_url,
+        has_key: has_key(&key),
+    })
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ProviderConfig {
-    pub api_key: Option<String>,
-    pub base_url: Option<String>,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(name: &str, api_key: Option<&str>) -> CustomProvider {
+        CustomProvider {
+            name: name.to_string(),
+            base_url: "http://localhost:8000/v1".to_string(),
+            api_key: api_key.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn redacts_every_key() {
+        let settings = redact_settings(AppSettings {
+            openai_api_key: Some("sk-secret".to_string()),
+            mistral_api_key: Some(" ".to_string()),
+            ollama_api_key: Some("token".to_string()),
+            openai_base_url: Some("http://proxy/v1".to_string()),
+            custom_providers: Some(vec![custom("gpu-box", Some("secret"))]),
+            ..Default::default()
+        });
+
+        assert_eq!(settings.openai_api_key.as_deref(), Some(REDACTED_KEY));
+        assert_eq!(settings.ollama_api_key.as_deref(), Some(REDACTED_KEY));
+        assert_eq!(settings.mistral_api_key, None);
+        assert_eq!(settings.openai_base_url.as_deref(), Some("http://proxy/v1"));
+        assert_eq!(
+            settings.custom_providers.unwrap()[0].api_key.as_deref(),
+            Some(REDACTED_KEY)
+        );
+    }
+
+    #[test]
+    fn keeps_stored_keys_when_redacted_settings_come_back() {
+        let mut base = AppSettings {
+            openai_api_key: Some("sk-old".to_string()),
+            anthropic_api_key: Some("sk-ant-old".to_string()),
+            custom_providers: Some(vec![
+                custom("gpu-box", Some("box-key")),
+                custom("gateway", Some("gateway-key")),
+            ]),
+            ..Default::default()
+        };
+        let mut update = redact_settings(base.clone());
+        update.anthropic_api_key = Some(String::new());
+        update.xai_api_key = Some("xai-new".to_string());
+        update.custom_providers = Some(vec![
+            custom("renamed-box", Some(REDACTED_KEY)),
+            custom("gateway", Some(REDACTED_KEY)),
+        ]);
+
+        merge_settings(&mut base, update);
+
+        assert_eq!(base.openai_api_key.as_deref(), Some("sk-old"));
+        assert_eq!(base.anthropic_api_key.as_deref(), Some("sk-ant-old"));
+        assert_eq!(base.xai_api_key.as_deref(), Some("xai-new"));
+        let keys: Vec<_> = base
+            .custom_providers
+            .unwrap()
+            .into_iter()
+            .map(|p| p.api_key)
+            .collect();
+        assert_eq!(
+            keys,
+            [Some("box-key".to_string()), Some("gateway-key".to_string())]
+        );
+    }
 }
@@ -1,3 +1,4 @@
+use crate::modules::error::{AppError, AppResult};
 use serde::Serialize;
 use std::sync::Mutex;
 use sysinfo::System;
@@ -43,8 +44,8 @@ pub struct UsageSnapshot {
 }
 
 #[tauri::command]
-pub async fn get_hardware_info(state: tauri::State<'_, AppSys>) -> Result<HardwareInfo, String> {
-    let mut sys = state.0.lock().map_err(|_| "lock poisoned")?;
+pub async fn get_hardware_info(state: tauri::State<'_, AppSys>) -> AppResult<HardwareInfo> {
+    let mut sys = state.0.lock()?;
     if sys.cpus().is_empty() {
         *sys = System::new_all();
     }
@@ -143,8 +144,8 @@ pub async fn get_hardware_info(state: tauri::State<'_, AppSys>) -> Result<Hardwa
 }
 
 #[tauri::command]
-pub async fn get_usage_snapshot(state: tauri::State<'_, AppSys>) -> Result<UsageSnapshot, String> {
-    let mut sys = state.0.lock().map_err(|_| "lock poisoned")?;
+pub async fn get_usage_snapshot(state: tauri::State<'_, AppSys>) -> AppResult<UsageSnapshot> {
+    let mut sys = state.0.lock()?;
     sys.refresh_cpu();
     sys.refresh_memory();
     let cpu = sys.global_cpu_info().cpu_usage() as f64;
@@ -164,83 +165,69 @@ pub async fn get_usage_snapshot(state: tauri::State<'_, AppSys>) -> Result<Usage
 }
 
 #[tauri::command]
-pub async fn open_path_in_explorer(path: String) -> Result<(), String> {
+pub async fn open_path_in_explorer(path: String) -> AppResult<()> {
     #[cfg(target_os = "windows")]
     {
-        std::process::Command::new("explorer")
-            .arg(path)
-            .spawn()
-            .map_err(|e| e.to_string())?;
+        std::process::Command::new("explorer").arg(path).spawn()?;
     }
     #[cfg(target_os = "macos")]
     {
-        std::process::Command::new("open")
-            .arg(path)
-            .spawn()
-            .map_err(|e| e.to_string())?;
+        std::process::Command::new("open").arg(path).spawn()?;
     }
     #[cfg(target_os = "linux")]
     {
-        std::process::Command::new("xdg-open")
-            .arg(path)
-            .spawn()
-            .map_err(|e| e.to_string())?;
+        std::process::Command::new("xdg-open").arg(path).spawn()?;
     }
     Ok(())
 }
 
 #[tauri::command]
-pub async fn reveal_path(path: String) -> Result<(), String> {
+pub async fn reveal_path(path: String) -> AppResult<()> {
     #[cfg(target_os = "windows")]
     {
         std::process::Command::new("explorer")
             .args(["/select,", &path])
-            .spawn()
-            .map_err(|e| e.to_string())?;
+            .spawn()?;
     }
     #[cfg(target_os = "macos")]
     {
         std::process::Command::new("open")
             .args(["-R", &path])
-            .spawn()
-            .map_err(|e| e.to_string())?;
+            .spawn()?;
     }
     #[cfg(target_os = "linux")]
     {
         // Fallback: open the containing directory
         let p = std::path::Path::new(&path);
         let dir = p.parent().unwrap_or_else(|| std::path::Path::new("."));
-        std::process::Command::new("xdg-open")
-            .arg(dir)
-            .spawn()
-            .map_err(|e| e.to_string())?;
+        std::process::Command::new("xdg-open").arg(dir).spawn()?;
     }
     Ok(())
 }
 
 #[tauri::command]
-pub async fn get_username() -> Result<String, String> {
+pub async fn get_username() -> AppResult<String> {
     #[cfg(target_os = "windows")]
     {
         use std::env;
         env::var("USERNAME")
             .or_else(|_| env::var("USER"))
-            .map_err(|e| format!("Failed to get username: {}", e))
+            .map_err(|e| AppError::not_found(format!("Failed to get username: {}", e)))
     }
     #[cfg(target_os = "macos")]
     {
         use std::env;
-        env::var("USER").map_err(|e| format!("Failed to get username: {}", e))
+        env::var("USER").map_err(|e| AppError::not_found(format!("Failed to get username: {}", e)))
     }
     #[cfg(target_os = "linux")]
     {
         use std::env;
         env::var("USER")
             .or_else(|_| env::var("LOGNAME"))
-            .map_err(|e| format!("Failed to get username: {}", e))
+            .map_err(|e| AppError::not_found(format!("Failed to get username: {}", e)))
     }
     #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
     {
-        Err("Unsupported platform".to_string())
+        Err(AppError::internal("Unsupported platform"))
     }
 }
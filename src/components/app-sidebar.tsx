@@ -8,7 +8,7 @@ import {
 	Search,
 	X,
 } from "lucide-react";
-import { useEffect, useState, useRef } from "react";
+import { useEffect, useState, useRef, type UIEvent } from "react";
 import {
 	listConversations,
 	createConversation,
@@ -49,6 +49,10 @@ export function AppSidebar() {
 	const [conversations, setConversations] = useState<Conversation[]>([]);
 	const [searchQuery, setSearchQuery] = useState("");
 	const searchInputRef = useRef<HTMLInputElement>(null);
+	// Cursor of the next older page; null once every conversation is loaded
+	const olderCursorRef = useRef<string | null>(null);
+	const loadedOlderRef = useRef(false);
+	const loadingOlderRef = useRef(false);
 
 	// Filter conversations based on search query
 	const filteredConversations = conversations.filter((conversation) => {
@@ -57,11 +61,30 @@ export function AppSidebar() {
 		return conversation.title.toLowerCase().includes(query);
 	});
 
+	const showConversations = (list: Conversation[]) => {
+		setConversations(list);
+		chatCache.setConversations(list);
+	};
+
+	// Reloads the newest page; older pages loaded by scrolling are kept after it
 	const refresh = async () => {
 		try {
-			const list = await listConversations();
-			setConversations(list);
-			chatCache.setConversations(list);
+			const page = await listConversations();
+			if (loadedOlderRef.current) {
+				setConversations((current) => {
+					const list = [
+						...page.items,
+						...current.filter(
+							(c) => !page.items.some((item) => item.id === c.id),
+						),
+					];
+					chatCache.setConversations(list);
+					return list;
+				});
+			} else {
+				olderCursorRef.current = page.older_cursor;
+				showConversations(page.items);
+			}
 
 			// Preload recent conversations in background
 			setTimeout(() => {
@@ -71,11 +94,47 @@ export function AppSidebar() {
 				}
 			}, 1000); // Wait 1 second after loading conversations
 		} catch {
-			setConversations([]);
-			chatCache.setConversations([]);
+			showConversations([]);
 		}
 	};
 
+	const loadOlder = async () => {
+		const before = olderCursorRef.current;
+		if (!before || loadingOlderRef.current) return;
+		loadingOlderRef.current = true;
+		try {
+			const page = await listConversations({ before });
+			olderCursorRef.current = page.older_cursor;
+			loadedOlderRef.current = true;
+			setConversations((current) => {
+				const list = [
+					...current,
+					...page.items.filter(
+						(item) => !current.some((c) => c.id === item.id),
+					),
+				];
+				chatCache.setConversations(list);
+				return list;
+			});
+		} catch (error) {
+			console.warn("Failed to load older conversations:", error);
+		} finally {
+			loadingOlderRef.current = false;
+		}
+	};
+
+	// The next page is fetched as the list is scrolled near its end
+	const handleScroll = (e: UIEvent<HTMLDivElement>) => {
+		const el = e.currentTarget;
+		if (el.scrollTop + el.clientHeight >= el.scrollHeight - 100) {
+			void loadOlder();
+		}
+	};
+
+	// Removed conversations may sit in an older page, which refresh does not reload
+	const removeConversation = (id: string) =>
+		setConversations((current) => current.filter((c) => c.id !== id));
+
 	// Handle hover on conversation items for preloading
 	const handleConversationHover = (conversationId: string) => {
 		if (
@@ -166,7 +225,7 @@ export function AppSidebar() {
 					)}
 				</div>
 			</SidebarHeader>
-			<SidebarContent>
+			<SidebarContent onScroll={handleScroll}>
 				<SidebarGroup>
 					<SidebarGroupLabel>
 						{searchQuery ? `Search Results` : "Recents"}
@@ -245,6 +304,7 @@ export function AppSidebar() {
 														variant="destructive"
 														onClick={async () => {
 															await deleteConversation(c.id);
+															removeConversation(c.id);
 															await refresh();
 														}}
 													>
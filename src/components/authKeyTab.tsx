@@ -22,6 +22,7 @@ import {
 	FormMessage,
 } from "@/components/ui/form";
 import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "@/lib/errors";
 
 const TetherFormSchema = z.object({
 	apiKey: z.string().min(2, {
@@ -60,7 +61,7 @@ export default function authKeyTab() {
 			});
 			window.location.href = "/dashboard";
 		} catch (err) {
-			toast("Failed to save settings", { description: String(err) });
+			toast("Failed to save settings", { description: errorMessage(err) });
 		}
 	}
 	async function onTetherSubmit(data: z.infer<typeof TetherFormSchema>) {
@@ -68,7 +69,7 @@ export default function authKeyTab() {
 			await invoke("save_settings", { apiKey: data.apiKey, apiUrl: null });
 			window.location.href = "/dashboard";
 		} catch (err) {
-			toast("Failed to save settings", { description: String(err) });
+			toast("Failed to save settings", { description: errorMessage(err) });
 		}
 	}
 	return (
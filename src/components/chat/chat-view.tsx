@@ -27,6 +27,7 @@ import {
 // Import new components
 import { EmptyChatState } from "./empty-chat-state";
 import { MessageList } from "./message-list";
+import { OlderMessagesLoader } from "./older-messages-loader";
 
 // Import custom hook
 import { useChatLogic } from "@/hooks/use-chat-logic";
@@ -49,6 +50,8 @@ export default function ChatView({
 		setUseReasoning,
 		allMessages,
 		isLoadingMessages,
+		hasOlderMessages,
+		loadOlderMessages,
 		status,
 		onSend,
 		onCopy,
@@ -79,6 +82,10 @@ export default function ChatView({
 					)}
 				</ConversationContent>
 				<ConversationScrollButton />
+				<OlderMessagesLoader
+					hasOlderMessages={hasOlderMessages}
+					onLoadOlder={loadOlderMessages}
+				/>
 			</Conversation>
 
 			<div className="px-4 pb-4">
@@ -0,0 +1,38 @@
+import { type FC, useEffect } from "react";
+import { useStickToBottomContext } from "use-stick-to-bottom";
+
+// Distance from the top, in pixels, at which the next older page is fetched
+const LOAD_THRESHOLD = 100;
+
+// Loads older messages as the conversation is scrolled to its top. Must be
+// rendered inside <Conversation>, whose scroll container it watches.
+export const OlderMessagesLoader: FC<{
+	hasOlderMessages: boolean;
+	onLoadOlder: () => Promise<void>;
+}> = ({ hasOlderMessages, onLoadOlder }) => {
+	const { scrollRef } = useStickToBottomContext();
+
+	useEffect(() => {
+		const scrollElement = scrollRef.current;
+		if (!scrollElement || !hasOlderMessages) return;
+
+		let loading = false;
+		const handleScroll = async () => {
+			if (loading || scrollElement.scrollTop > LOAD_THRESHOLD) return;
+			loading = true;
+			const previousHeight = scrollElement.scrollHeight;
+			await onLoadOlder();
+			// Keep the messages in view where they were once the older ones are prepended
+			requestAnimationFrame(() => {
+				scrollElement.scrollTop +=
+					scrollElement.scrollHeight - previousHeight;
+				loading = false;
+			});
+		};
+
+		scrollElement.addEventListener("scroll", handleScroll);
+		return () => scrollElement.removeEventListener("scroll", handleScroll);
+	}, [scrollRef, hasOlderMessages, onLoadOlder]);
+
+	return null;
+};
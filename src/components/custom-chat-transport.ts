@@ -1,25 +1,24 @@
 import type { UIMessage } from "@ai-sdk/react";
-import {
-  convertToModelMessages,
-  streamText,
-  type ChatRequestOptions,
-  type ChatTransport,
-  type LanguageModel,
-  type UIMessageChunk,
-} from "ai";
+import type { ChatRequestOptions, ChatTransport, UIMessageChunk } from "ai";
 import { toast } from "sonner";
+import { errorMessage, type AppError } from "@/lib/errors";
+import { generateUUID } from "@/lib/utils";
 
+// Thinking tokens requested when reasoning is switched on
+const REASONING_BUDGET = 8192;
+
+// Every reply is streamed by the backend, so provider keys never reach the webview
 export class CustomChatTransport implements ChatTransport<UIMessage> {
-  private model: LanguageModel;
+  private modelId?: string;
   private adapterKind?: string;
 
-  constructor(model: LanguageModel, adapterKind?: string) {
-    this.model = model;
+  constructor(modelId?: string, adapterKind?: string) {
+    this.modelId = modelId;
     this.adapterKind = adapterKind;
   }
 
-  updateModel(model: LanguageModel, adapterKind?: string) {
-    this.model = model;
+  updateModel(modelId?: string, adapterKind?: string) {
+    this.modelId = modelId;
     this.adapterKind = adapterKind;
   }
 
@@ -33,128 +32,50 @@ export class CustomChatTransport implements ChatTransport<UIMessage> {
       messageId: string | undefined;
     } & ChatRequestOptions,
   ): Promise<ReadableStream<UIMessageChunk>> {
-    // Check if this is an Ollama model (should go directly to backend)
-    if (await this.isOllamaModel()) {
-      return this.sendOllamaMessages(options);
+    const modelId = this.modelId;
+    if (!modelId) {
+      throw new Error("No model selected");
     }
+    void this.warnIfOverContext(modelId, options.messages);
 
-    // Get reasoning preference from request body
-    const useReasoning = (options.body as any)?.reasoning ?? false;
+    const { backendAdapterKind } = await import("@/components/providers");
+    return this.sendBackendMessages(
+      options,
+      modelId,
+      await backendAdapterKind(modelId, this.adapterKind),
+    );
+  }
 
-    const result = streamText({
-      model: this.model,
-      messages: convertToModelMessages(options.messages),
-      onError({ error }) {
-        console.error(error);
-        toast.error("An error occurred", {
-          description: error instanceof Error ? error.message : String(error),
-        });
-      },
-      onFinish({ text, finishReason, usage, totalUsage, warnings }) {
-        // Log completion details
-        console.log("Response complete:", {
-          finishReason,
-          textLength: text?.length,
-          totalTokens: totalUsage?.totalTokens,
-        });
+  // Rough estimate (about 4 characters per token of text) so an overlong conversation
+  // is flagged before the provider truncates or rejects it
+  private async warnIfOverContext(modelId: string, messages: UIMessage[]) {
+    try {
+      const { getModelCapabilities } = await import("@/lib/chat");
+      const contextLength = (
+        await getModelCapabilities(modelId, this.adapterKind)
+      )?.context_length;
+      if (!contextLength) return;
 
-        // Show warnings as toasts
-        if (warnings && warnings.length > 0) {
-          for (const warning of warnings) {
-            const warningMessage = 
-              warning.type === "unsupported-setting"
-                ? `Unsupported setting: ${warning.setting}${warning.details ? ` - ${warning.details}` : ""}`
-                : typeof warning === "string"
-                ? warning
-                : JSON.stringify(warning);
-            
-            toast.warning("AI SDK Warning", {
-              description: warningMessage,
-              duration: 5000,
-            });
+      let characters = 0;
+      for (const message of messages) {
+        for (const part of message.parts) {
+          if (part.type === "text" || part.type === "reasoning") {
+            characters += part.text.length;
           }
         }
-
-        // Dispatch event to save the message with complete text
-        setTimeout(() => {
-          const event = new CustomEvent("ai-response-finished", {
-            detail: {
-              finishReason,
-              usage,
-              totalUsage,
-              chatId: options.chatId,
-              text, // Include the complete text
-              timestamp: Date.now(),
-            },
-          });
-          window.dispatchEvent(event);
-        }, 100);
-      },
-      abortSignal: options.abortSignal,
-      toolChoice: "auto",
-      providerOptions: {
-        openai: useReasoning ? {
-          reasoningSummary: "detailed", // Enable detailed reasoning if requested
-        } : {},
-        google: useReasoning ? {
-          thinkingConfig: {
-            includeThoughts: true,
-            thinkingBudget: 8192,
-          },
-        } : {},
-      },
-    });
-
-    console.log("result from ai sdk", result);
-    console.log("Cached tokens:", result.providerMetadata);
-
-    return result.toUIMessageStream({
-      sendReasoning: true,
-      onError: (error) => {
-        // Note: By default, the AI SDK will return "An error occurred",
-        // which is intentionally vague in case the error contains sensitive information like API keys.
-        // If you want to provide more detailed error messages, keep the code below. Otherwise, remove this onError callback.
-        if (error == null) {
-          return "Unknown error";
-        }
-        if (typeof error === "string") {
-          return error;
-        }
-        if (error instanceof Error) {
-          return error.message;
-        }
-        return JSON.stringify(error);
-      },
-    });
-  }
-
-  private async isOllamaModel(): Promise<boolean> {
-    // Use adapter_kind if available for reliable provider detection
-    if (this.adapterKind) {
-      const { getProviderKeyFromDisplayName } = await import("@/components/providers");
-      const providerKey = getProviderKeyFromDisplayName(this.adapterKind);
-      return providerKey === "ollama";
-    }
-    
-    // Fallback: Check model ID if adapter_kind is not available
-    const modelId = (this.model as any)?.modelId || this.model.toString();
-    
-    // Ollama models don't have "/" in their ID, while provider models do (e.g., "openai/gpt-4")
-    const hasProviderPrefix = modelId.includes("/");
-    if (hasProviderPrefix) {
-      return false; // This is a provider model (OpenRouter, etc.), not Ollama
+      }
+      const estimatedTokens = Math.ceil(characters / 4);
+      if (estimatedTokens > contextLength) {
+        toast.warning("Conversation may not fit the model", {
+          description: `About ${estimatedTokens.toLocaleString()} tokens; ${modelId} accepts ${contextLength.toLocaleString()}.`,
+        });
+      }
+    } catch (error) {
+      console.warn("Failed to check context length:", error);
     }
-    
-    // Only check for colon after confirming no provider prefix
-    return (
-      modelId.includes(":") ||
-      modelId.startsWith("llama") ||
-      modelId.startsWith("qwen") ||
-      modelId.startsWith("codellama")
-    );
   }
 
-  private async sendOllamaMessages(
+  private async sendBackendMessages(
     options: {
       chatId: string;
       messages: UIMessage[];
@@ -163,65 +84,166 @@ export class CustomChatTransport implements ChatTransport<UIMessage> {
       trigger: "submit-message" | "regenerate-message";
       messageId: string | undefined;
     } & ChatRequestOptions,
+    modelId: string,
+    adapterKind: string,
   ): Promise<ReadableStream<UIMessageChunk>> {
     const { invoke } = await import("@tauri-apps/api/core");
+    const { listen } = await import("@tauri-apps/api/event");
 
-    // Convert UI messages to the format expected by backend
+    // The backend translates the full UI message history (all roles and parts)
     const backendMessages = options.messages.map((msg) => ({
       role: msg.role,
-      content:
-        msg.parts
-          ?.map((part) => {
-            if (part.type === "text") return part.text;
-            if (part.type === "reasoning") return part.text; // Include reasoning content
-            return "";
-          })
-          .join("") ||
-        (msg as any).content ||
-        "",
+      parts: msg.parts,
     }));
 
-    const modelId = (this.model as any)?.modelId || this.model.toString();
+    const conversationId = options.chatId;
+    // The reply keeps one id in the UI and in the backend, which saves it when cancelled,
+    // so both saves land on the same row
+    const messageId = options.messageId ?? generateUUID();
+    const textId = messageId;
+    const useReasoning = (options.body as any)?.reasoning ?? false;
+
+    type StreamEvent = {
+      conversation_id: string;
+      message_id: string;
+      token?: string;
+      signature?: string | null;
+      complete_content?: string;
+      usage?: { input_tokens: number; output_tokens: number } | null;
+      error?: AppError;
+    };
 
-    try {
-      const tokens: string[] = await invoke("stream_ollama_chat", {
-        model: modelId,
-        messages: backendMessages,
-      });
-
-      // Convert tokens to UIMessageChunk stream
-      return new ReadableStream({
-        start(controller) {
-          let index = 0;
-          const sendNext = () => {
-            if (index < tokens.length) {
+    // Tokens arrive as backend events; forward the ones for this conversation
+    return new ReadableStream<UIMessageChunk>({
+      async start(controller) {
+        const unlisteners: Array<() => void> = [];
+        const finish = () => {
+          for (const unlisten of unlisteners) unlisten();
+          controller.close();
+        };
+        const isOurs = (payload: StreamEvent) =>
+          payload.conversation_id === conversationId;
+
+        // Reasoning blocks come before the text; each one becomes its own part
+        let reasoningId: string | undefined;
+        let reasoningBlocks = 0;
+        let textStarted = false;
+        const endReasoning = (signature?: string | null) => {
+          if (!reasoningId) return;
+          controller.enqueue({
+            type: "reasoning-end" as const,
+            id: reasoningId,
+            // Anthropic needs the signature to accept the block in later turns
+            ...(signature && {
+              providerMetadata: { anthropic: { signature } },
+            }),
+          });
+          reasoningId = undefined;
+        };
+        const endParts = () => {
+          endReasoning();
+          if (textStarted) {
+            controller.enqueue({ type: "text-end" as const, id: textId });
+          }
+        };
+
+        unlisteners.push(
+          await listen<StreamEvent>("chat_stream_reasoning", ({ payload }) => {
+            if (!isOurs(payload)) return;
+            if (!reasoningId) {
+              reasoningId = `${textId}-reasoning-${reasoningBlocks++}`;
+              controller.enqueue({ type: "reasoning-start" as const, id: reasoningId });
+            }
+            if (payload.token) {
               controller.enqueue({
-                type: "text-delta" as const,
-                delta: tokens[index],
-                id: options.messageId || "ollama-message",
+                type: "reasoning-delta" as const,
+                delta: payload.token,
+                id: reasoningId,
               });
-              index++;
-              // Small delay to simulate streaming
-              setTimeout(sendNext, 10);
-            } else {
-              controller.close();
             }
-          };
-          sendNext();
-        },
-      });
-    } catch (error) {
-      // Return error as a stream
-      return new ReadableStream({
-        start(controller) {
+            if (payload.signature) endReasoning(payload.signature);
+          }),
+          await listen<StreamEvent>("chat_stream_token", ({ payload }) => {
+            if (!isOurs(payload)) return;
+            endReasoning();
+            if (!textStarted) {
+              controller.enqueue({ type: "text-start" as const, id: textId });
+              textStarted = true;
+            }
+            controller.enqueue({
+              type: "text-delta" as const,
+              delta: payload.token ?? "",
+              id: textId,
+            });
+          }),
+          await listen<StreamEvent>("chat_stream_end", ({ payload }) => {
+            if (!isOurs(payload)) return;
+            endParts();
+            finish();
+            // Saves the reply again with its usage, in the AI SDK's shape
+            const usage = payload.usage;
+            window.dispatchEvent(
+              new CustomEvent("ai-response-finished", {
+                detail: {
+                  chatId: conversationId,
+                  messageId,
+                  text: payload.complete_content,
+                  totalUsage: usage && {
+                    inputTokens: usage.input_tokens,
+                    outputTokens: usage.output_tokens,
+                    totalTokens: usage.input_tokens + usage.output_tokens,
+                  },
+                  timestamp: Date.now(),
+                },
+              }),
+            );
+          }),
+          await listen<StreamEvent>("chat_stream_cancelled", ({ payload }) => {
+            if (!isOurs(payload)) return;
+            endParts();
+            finish();
+          }),
+          await listen<StreamEvent>("chat_stream_error", ({ payload }) => {
+            if (!isOurs(payload)) return;
+            controller.enqueue({
+              type: "error" as const,
+              errorText: payload.error?.message ?? `${adapterKind} error`,
+            });
+            finish();
+          }),
+        );
+
+        try {
+          controller.enqueue({ type: "start" as const, messageId });
+          const { message_id } = await invoke<StreamEvent>("stream_provider_chat", {
+            input: {
+              conversation_id: conversationId,
+              message_id: messageId,
+              adapter_kind: adapterKind,
+              model: modelId,
+              messages: backendMessages,
+              options: useReasoning
+                ? { thinking_budget: REASONING_BUDGET }
+                : undefined,
+            },
+          });
+
+          // Stopping in the UI aborts the backend generation and keeps the partial reply
+          options.abortSignal?.addEventListener("abort", () => {
+            invoke("cancel_generation", {
+              conversationId,
+              messageId: message_id,
+            }).catch(console.error);
+          });
+        } catch (error) {
           controller.enqueue({
             type: "error" as const,
-            errorText: error instanceof Error ? error.message : "Ollama error",
+            errorText: errorMessage(error),
           });
-          controller.close();
-        },
-      });
-    }
+          finish();
+        }
+      },
+    });
   }
 
   async reconnectToStream(
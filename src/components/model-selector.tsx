@@ -1,5 +1,5 @@
 import { useEffect, useMemo, useState } from "react";
-import { ChevronsUpDown, Check } from "lucide-react";
+import { ChevronsUpDown, Check, RefreshCw, Eye, Wrench, Brain } from "lucide-react";
 import { Button } from "@/components/ui/button";
 import {
 	Command,
@@ -14,19 +14,17 @@ import {
 	PopoverContent,
 	PopoverTrigger,
 } from "@/components/ui/popover";
+import type { AppError } from "@/lib/errors";
 import { cn } from "@/lib/utils";
 import {
 	listChatModels,
+	refreshChatModels,
 	type ListedModel,
+	type ModelCapabilities,
 	getConversation,
 	updateConversationModel,
 } from "../lib/chat";
-import {
-	PROVIDERS,
-	fetchProviderModels,
-	getProviderKeyFromDisplayName,
-} from "../components/providers";
-import { getCachedProviders } from "../lib/api-keys";
+import { getProviderKeyFromDisplayName } from "../components/providers";
 import ModelIcon, { type ModelType } from "@/components/model-icon";
 
 function toIcon(adapter: string): ModelType {
@@ -61,102 +59,83 @@ function toIcon(adapter: string): ModelType {
 //   }
 // }
 
+type CapabilityFilter = "vision" | "tools" | "reasoning";
+
+const capabilityFilters: { key: CapabilityFilter; label: string }[] = [
+	{ key: "vision", label: "Vision" },
+	{ key: "tools", label: "Tools" },
+	{ key: "reasoning", label: "Reasoning" },
+];
+
+// e.g. 128000 -> "128K", 1048576 -> "1M"
+function formatContext(tokens: number): string {
+	if (tokens >= 1_000_000) return `${Math.round(tokens / 1_048_576)}M`;
+	return `${Math.round(tokens / 1000)}K`;
+}
+
+function CapabilityBadges({ capabilities }: { capabilities?: ModelCapabilities }) {
+	if (!capabilities) return null;
+	return (
+		<span className="ml-auto flex items-center gap-1 pl-2 opacity-60">
+			{capabilities.vision && <Eye size={12} aria-label="Vision" />}
+			{capabilities.tools && <Wrench size={12} aria-label="Tools" />}
+			{capabilities.reasoning && <Brain size={12} aria-label="Reasoning" />}
+			{capabilities.context_length && (
+				<span className="text-[10px]">
+					{formatContext(capabilities.context_length)}
+				</span>
+			)}
+		</span>
+	);
+}
+
 export default function ModelSelector({
 	conversationId,
 }: { conversationId?: string }) {
 	const [open, setOpen] = useState(false);
 	const [models, setModels] = useState<ListedModel[]>([]);
+	// Providers whose settings could not be used, with the reason
+	const [unavailable, setUnavailable] = useState<AppError[]>([]);
 	const [selected, setSelected] = useState<string | undefined>(undefined);
+	const [refreshing, setRefreshing] = useState(false);
+	const [filters, setFilters] = useState<CapabilityFilter[]>([]);
 
-	// Get available AI SDK models from providers (will be loaded dynamically)
-	const [aiSdkModels, setAiSdkModels] = useState<ListedModel[]>([]);
-	const [modelsLoading, setModelsLoading] = useState(true);
-
-	// Load AI SDK models dynamically
-	useEffect(() => {
-		(async () => {
-			try {
-				setModelsLoading(true);
-				const cachedProviders = getCachedProviders();
-				const availableModels: ListedModel[] = [];
-
-				// Fetch models dynamically for each provider that has API keys
-				const modelFetchPromises = cachedProviders.map(async (providerName) => {
-					const provider = PROVIDERS[providerName];
-					if (provider) {
-						try {
-							const dynamicModels = await fetchProviderModels(providerName);
-							dynamicModels.forEach((model) => {
-								availableModels.push({
-									model,
-									adapter_kind: provider.displayName,
-									enabled: true,
-								});
-							});
-						} catch (error) {
-							console.warn(
-								`Failed to fetch models for ${providerName}:`,
-								error,
-							);
-							// Fallback to hardcoded models
-							provider.models.forEach((model) => {
-								availableModels.push({
-									model,
-									adapter_kind: provider.displayName,
-									enabled: true,
-								});
-							});
-						}
-					}
-				});
-
-				await Promise.all(modelFetchPromises);
+	// Models missing a filtered capability are hidden; unknown counts as missing
+	const visibleModels = useMemo(
+		() =>
+			models.filter((m) =>
+				filters.every((filter) => m.capabilities?.[filter] === true),
+			),
+		[models, filters],
+	);
 
-				console.log("Dynamic AI SDK Models:", availableModels);
-				console.log("Cached Providers:", cachedProviders);
+	const toggleFilter = (filter: CapabilityFilter) =>
+		setFilters((current) =>
+			current.includes(filter)
+				? current.filter((f) => f !== filter)
+				: [...current, filter],
+		);
 
-				setAiSdkModels(availableModels);
-			} catch (error) {
-				console.error("Failed to load AI SDK models:", error);
-			} finally {
-				setModelsLoading(false);
-			}
-		})();
-	}, []);
+	const refreshModels = async () => {
+		setRefreshing(true);
+		try {
+			const { models, unavailable } = await refreshChatModels();
+			setModels(models);
+			setUnavailable(unavailable);
+		} catch (error) {
+			console.error("Failed to refresh models:", error);
+		} finally {
+			setRefreshing(false);
+		}
+	};
 
 	useEffect(() => {
 		(async () => {
 			try {
-				// Wait for AI SDK models to load
-				if (modelsLoading) return;
-
-				// First, try to initialize Google provider to ensure it's available
-				try {
-					const { initializeGoogle } = await import(
-						"../components/providers/google"
-					);
-					await initializeGoogle();
-					console.log("Google provider initialized successfully");
-				} catch (error) {
-					console.warn("Google provider not available:", error);
-				}
-
-				const backendModels = await listChatModels();
-				console.log("Backend Models:", backendModels);
-
-				// Merge backend models with AI SDK models
-				const allModels = [
-					...backendModels,
-					...aiSdkModels.filter(
-						(sdkModel) =>
-							!backendModels.some(
-								(backendModel) => backendModel.model === sdkModel.model,
-							),
-					),
-				];
-
-				console.log("All Models:", allModels);
+				// Every provider's models are listed by the backend, which holds the API keys
+				const { models: allModels, unavailable } = await listChatModels();
 				setModels(allModels);
+				setUnavailable(unavailable);
 				// All models from backend are now enabled by default if API key is set
 				const def = allModels[0]?.model;
 
@@ -192,7 +171,7 @@ export default function ModelSelector({
 				}
 			} catch {}
 		})();
-	}, [aiSdkModels, modelsLoading]);
+	}, []);
 
 	// Load conversation's stored model when conversationId changes
 	useEffect(() => {
@@ -248,7 +227,11 @@ export default function ModelSelector({
 							/>
 							<span className="hidden sm:block truncate max-w-[180px]">
 								{selectedMeta.model}
-								{!selectedMeta.enabled ? " (API key required)" : ""}
+								{selectedMeta.error
+									? " (unavailable)"
+									: !selectedMeta.enabled
+										? " (API key required)"
+										: ""}
 							</span>
 						</>
 					) : (
@@ -260,10 +243,25 @@ export default function ModelSelector({
 			<PopoverContent className="w-[320px] p-0">
 				<Command>
 					<CommandInput placeholder="Search model..." className="h-9" />
+					<div className="flex gap-1 px-2 py-1.5 border-b border-white/10">
+						{capabilityFilters.map(({ key, label }) => (
+							<button
+								key={key}
+								type="button"
+								className={cn(
+									"px-2 py-0.5 text-[11px] rounded-full border border-white/10",
+									filters.includes(key) ? "bg-white/20" : "opacity-60",
+								)}
+								onClick={() => toggleFilter(key)}
+							>
+								{label}
+							</button>
+						))}
+					</div>
 					<CommandList>
 						<CommandEmpty>No models found.</CommandEmpty>
 						<CommandGroup>
-							{models.map((m) => (
+							{visibleModels.map((m) => (
 								<CommandItem
 									key={m.model}
 									value={m.model}
@@ -309,9 +307,10 @@ export default function ModelSelector({
 										model={toIcon(m.adapter_kind)}
 									/>
 									<span className="truncate">{m.model}</span>
+									<CapabilityBadges capabilities={m.capabilities} />
 									<Check
 										className={cn(
-											"ml-auto",
+											m.capabilities ? "ml-1" : "ml-auto",
 											selected === m.model ? "opacity-100" : "opacity-0",
 										)}
 									/>
@@ -319,6 +318,27 @@ export default function ModelSelector({
 							))}
 						</CommandGroup>
 					</CommandList>
+					{unavailable.map((error) => (
+						<div
+							key={error.provider ?? error.message}
+							className="px-3 py-1.5 text-[11px] text-red-400 border-t border-white/10"
+							title={error.details ?? undefined}
+						>
+							{error.provider} unavailable: {error.message}
+						</div>
+					))}
+					<button
+						type="button"
+						className="flex items-center gap-2 px-3 py-2 text-xs opacity-70 hover:opacity-100 border-t border-white/10 disabled:opacity-40"
+						onClick={refreshModels}
+						disabled={refreshing}
+					>
+						<RefreshCw
+							size={12}
+							className={refreshing ? "animate-spin" : undefined}
+						/>
+						{refreshing ? "Refreshing models..." : "Refresh models"}
+					</button>
 				</Command>
 			</PopoverContent>
 		</Popover>
@@ -1,130 +1,3 @@
-import { initializeGoogle, fetchGoogleModels } from "./google";
-import { initializeOpenAI, fetchOpenAIModels } from "./openai";
-import { initializeAnthropic, fetchAnthropicModels } from "./anthropic";
-import { initializeGroq, fetchGroqModels } from "./groq";
-import { initializeOpenRouter, fetchOpenRouterModels } from "./openrouter";
-import { initializeOllama, fetchOllamaModels } from "./ollama";
-import { initializeDeepseek, fetchDeepSeekModels } from "./deepseek";
-import { google } from "./google";
-import { openai } from "./openai";
-import { anthropic } from "./anthropic";
-import { groq } from "./groq";
-import { openrouter } from "./openrouter";
-import { ollama } from "./ollama";
-import { deepseek } from "./deepseek";
-
-export interface ModelProvider {
-  name: string;
-  displayName: string;
-  models: string[];
-  initialize: () => Promise<void>;
-  getInstance: (model: string) => any;
-  fetchModels?: () => Promise<string[]>;
-}
-
-export const PROVIDERS: Record<string, ModelProvider> = {
-  google: {
-    name: "google",
-    displayName: "Google Gemini",
-    models: [
-      "gemini-2.0-flash-exp",
-      "gemini-1.5-flash",
-      "gemini-1.5-pro",
-      "gemini-1.0-pro",
-    ],
-    initialize: initializeGoogle,
-    getInstance: google,
-    fetchModels: fetchGoogleModels,
-  },
-  ollama: {
-    name: "ollama",
-    displayName: "Ollama",
-    models: [
-      "llama3.2:3b",
-      "llama3.1:8b",
-      "llama3.1:70b",
-      "qwen2.5:7b",
-      "codellama:7b",
-    ],
-    initialize: initializeOllama,
-    getInstance: ollama,
-    fetchModels: fetchOllamaModels,
-  },
-  openai: {
-    name: "openai",
-    displayName: "OpenAI",
-    models: ["gpt-4o", "gpt-4o-mini", "gpt-4-turbo", "gpt-4", "gpt-3.5-turbo"],
-    initialize: initializeOpenAI,
-    getInstance: openai,
-    fetchModels: fetchOpenAIModels,
-  },
-  anthropic: {
-    name: "anthropic",
-    displayName: "Anthropic Claude",
-    models: [
-      "claude-3-5-sonnet-20241022",
-      "claude-3-5-haiku-20241022",
-      "claude-3-opus-20240229",
-      "claude-3-sonnet-20240229",
-      "claude-3-haiku-20240307",
-    ],
-    initialize: initializeAnthropic,
-    getInstance: anthropic,
-    fetchModels: fetchAnthropicModels,
-  },
-  groq: {
-    name: "groq",
-    displayName: "Groq",
-    models: [
-      "llama-3.2-90b-text-preview",
-      "llama-3.2-11b-text-preview",
-      "llama-3.2-3b-preview",
-      "llama-3.2-1b-preview",
-      "llama-3.1-70b-versatile",
-      "llama-3.1-8b-instant",
-      "mixtral-8x7b-32768",
-    ],
-    initialize: initializeGroq,
-    getInstance: groq,
-    fetchModels: fetchGroqModels,
-  },
-  openrouter: {
-    name: "openrouter",
-    displayName: "OpenRouter",
-    models: [
-      "anthropic/claude-3.5-sonnet",
-      "anthropic/claude-3-haiku",
-      "openai/gpt-4o",
-      "openai/gpt-4o-mini",
-      "google/gemini-pro-1.5",
-      "google/gemini-flash-1.5",
-      "meta-llama/llama-3.2-90b-instruct",
-      "meta-llama/llama-3.1-405b-instruct",
-      "mistralai/mistral-large",
-      "mistralai/mistral-7b-instruct",
-      "mistralai/mistral-small-3.2-24b-instruct",
-      "x-ai/grok-4-fast:free",
-    ],
-    initialize: initializeOpenRouter,
-    getInstance: openrouter,
-    fetchModels: fetchOpenRouterModels,
-  },
-  deepseek: {
-    name: "deepseek",
-    displayName: "DeepSeek",
-    models: [
-      "deepseek-chat",
-      "deepseek-coder",
-      "deepseek-reasoner",
-      "deepseek-chat-67b",
-      "deepseek-coder-33b",
-    ],
-    initialize: initializeDeepseek,
-    getInstance: deepseek,
-    fetchModels: fetchDeepSeekModels,
-  },
-};
-
 /**
  * Maps provider display name (adapter_kind) to provider key
  */
@@ -139,103 +12,73 @@ export function getProviderKeyFromDisplayName(displayName: string): string {
   if (key.includes("openrouter")) return "openrouter";
   if (key.includes("ollama")) return "ollama";
   if (key.includes("deepseek")) return "deepseek";
+  if (key.includes("mistral")) return "mistral";
+  if (key === "x.ai" || key === "xai") return "xai";
   
   // Fallback to openrouter for unknown providers
   return "openrouter";
 }
 
-export function getProviderFromModel(model: any, adapterKind?: string) {
-  // If adapter_kind is provided, use it for more reliable provider detection
-  if (adapterKind) {
-    return getProviderKeyFromDisplayName(adapterKind);
-  }
-
-  // Check each provider's models
-  for (const [providerName, provider] of Object.entries(PROVIDERS)) {
-    if (provider.models.includes(model)) {
-      return providerName;
-    }
-  }
-
-  // Default fallback to openrouter for unknown models
-  console.log("Unknown model, falling back to openrouter:", model);
-  return "openrouter";
-}
-
-export async function initializeProvider(providerName: string): Promise<void> {
-  const provider = PROVIDERS[providerName];
-  if (!provider) {
-    throw new Error(`Unknown provider: ${providerName}`);
-  }
-
+/**
+ * Whether an adapter kind names a custom OpenAI-compatible endpoint from settings.
+ * The backend streams them like the built-in providers.
+ */
+export async function isCustomProvider(adapterKind?: string): Promise<boolean> {
+  if (!adapterKind) return false;
+  const { invoke } = await import("@tauri-apps/api/core");
   try {
-    await provider.initialize();
-    console.log(`${provider.displayName} provider initialized successfully`);
-  } catch (error) {
-    console.error(`Failed to initialize ${provider.displayName}:`, error);
-    throw error;
+    const settings = await invoke<{ custom_providers?: { name: string }[] | null }>(
+      "get_settings",
+    );
+    return (settings.custom_providers ?? []).some(
+      (provider) => provider.name.trim() === adapterKind,
+    );
+  } catch {
+    return false;
   }
 }
 
-export async function getModelInstance(model: string, adapterKind?: string) {
-  const providerName = getProviderFromModel(model, adapterKind);
-  const provider = PROVIDERS[providerName];
+// Backend adapter kind of each provider, by provider key
+const BACKEND_PROVIDERS: Record<string, string> = {
+  openai: "OpenAI",
+  anthropic: "Anthropic",
+  google: "Gemini",
+  groq: "Groq",
+  openrouter: "OpenRouter",
+  deepseek: "DeepSeek",
+  mistral: "Mistral",
+  xai: "X.AI",
+  ollama: "Ollama",
+};
 
-  if (!provider) {
-    throw new Error(`No provider found for model: ${model}`);
+// Ollama models don't have "/" in their ID, while provider models do (e.g., "openai/gpt-4")
+function looksLikeOllamaModel(modelId: string): boolean {
+  if (modelId.includes("/")) {
+    return false; // This is a provider model (OpenRouter, etc.), not Ollama
   }
 
-  try {
-    // Initialize provider if not already done
-    await provider.initialize();
-    return provider.getInstance(model);
-  } catch (error) {
-    console.error(`Failed to get model instance for ${model}:`, error);
-    throw error;
-  }
+  // Only check for colon after confirming no provider prefix
+  return (
+    modelId.includes(":") ||
+    modelId.startsWith("llama") ||
+    modelId.startsWith("qwen") ||
+    modelId.startsWith("codellama")
+  );
 }
 
-export async function fetchProviderModels(
-  providerName: string,
-): Promise<string[]> {
-  const provider = PROVIDERS[providerName];
-  if (!provider) {
-    throw new Error(`Unknown provider: ${providerName}`);
-  }
-
-  try {
-    if (provider.fetchModels) {
-      const dynamicModels = await provider.fetchModels();
-      if (dynamicModels && dynamicModels.length > 0) {
-        return dynamicModels;
-      }
-    }
-    // Fallback to hardcoded models if dynamic fetch fails or is not available
-    return provider.models;
-  } catch (error) {
-    console.warn(
-      `Failed to fetch dynamic models for ${providerName}, using fallback:`,
-      error,
-    );
-    return provider.models;
+/**
+ * The adapter kind the backend knows a model's provider by
+ */
+export async function backendAdapterKind(
+  modelId: string,
+  adapterKind?: string,
+): Promise<string> {
+  // Use adapter_kind if available for reliable provider detection
+  if (adapterKind) {
+    if (await isCustomProvider(adapterKind)) return adapterKind;
+    return BACKEND_PROVIDERS[getProviderKeyFromDisplayName(adapterKind)];
   }
-}
-
-export async function getAllAvailableModels(): Promise<{
-  [providerName: string]: string[];
-}> {
-  const allModels: { [providerName: string]: string[] } = {};
-
-  const fetchPromises = Object.keys(PROVIDERS).map(async (providerName) => {
-    try {
-      const models = await fetchProviderModels(providerName);
-      allModels[providerName] = models;
-    } catch (error) {
-      console.warn(`Failed to fetch models for ${providerName}:`, error);
-      allModels[providerName] = PROVIDERS[providerName].models;
-    }
-  });
 
-  await Promise.all(fetchPromises);
-  return allModels;
+  // Fallback: guess from the model ID; unknown models go to OpenRouter
+  return looksLikeOllamaModel(modelId) ? "Ollama" : "OpenRouter";
 }
@@ -30,7 +30,7 @@ export function useChatLogic(conversationId?: string) {
     setConvId(conversationId);
   }, [conversationId]);
 
-  // AI SDK integration - using dynamically selected model
+  // The selected model, which the backend calls by id and adapter kind
   const currentModel = selectedModel as string;
   const { model } = useModelManager(currentModel, selectedModelAdapter);
 
@@ -39,7 +39,7 @@ export function useChatLogic(conversationId?: string) {
     messages: newMessages,
     sendMessage,
     status,
-  } = useCustomChat(model, selectedModelAdapter, {
+  } = useCustomChat(currentModel, selectedModelAdapter, {
     id: convId,
     experimental_throttle: 100,
     generateId: generateUUID,
@@ -48,6 +48,8 @@ export function useChatLogic(conversationId?: string) {
   const {
     allMessages,
     isLoadingMessages,
+    hasOlderMessages,
+    loadOlderMessages,
     titleGeneratedRef,
     messagesSinceLastTitleUpdateRef,
     lastTitleUpdateRef,
@@ -76,7 +78,7 @@ export function useChatLogic(conversationId?: string) {
   useEffect(() => {
     (async () => {
       try {
-        const list = await listChatModels();
+        const { models: list } = await listChatModels();
         setModels(list);
 
         if (list.length > 0) {
@@ -185,6 +187,8 @@ export function useChatLogic(conversationId?: string) {
     setUseReasoning,
     allMessages,
     isLoadingMessages,
+    hasOlderMessages,
+    loadOlderMessages,
     status,
     onSend,
     onCopy,
@@ -4,7 +4,7 @@ import type {
 	// useChat as useChatSDK,
 } from "@ai-sdk/react";
 import { useChat as useChatZustand } from "ai-sdk-zustand";
-import type { ChatInit, LanguageModel } from "ai";
+import type { ChatInit } from "ai";
 import { useEffect, useRef } from "react";
 import { CustomChatTransport } from "../components/custom-chat-transport";
 
@@ -15,21 +15,21 @@ type CustomChatOptions = Omit<ChatInit<UIMessage>, "transport"> &
 // It implements model switching and uses the custom chat transport,
 // making a nice reusable hook for chat functionality.
 export function useChat(
-	model: LanguageModel,
+	modelId: string | undefined,
 	adapterKind?: string,
 	options?: CustomChatOptions & { initialMessages?: UIMessage[] },
 ) {
 	const transportRef = useRef<CustomChatTransport | null>(null); // Using a ref here so we can update the model used in the transport without having to reload the page or recreate the transport
 
 	if (!transportRef.current) {
-		transportRef.current = new CustomChatTransport(model, adapterKind);
+		transportRef.current = new CustomChatTransport(modelId, adapterKind);
 	}
 
 	useEffect(() => {
 		if (transportRef.current) {
-			transportRef.current.updateModel(model, adapterKind);
+			transportRef.current.updateModel(modelId, adapterKind);
 		}
-	}, [model, adapterKind]);
+	}, [modelId, adapterKind]);
 
 	const chatResult = useChatZustand({
 		transport: transportRef.current,
@@ -1,5 +1,5 @@
 import { useState, useEffect, useRef } from "react";
-import { getMessages, saveCompleteMessage } from "@/lib/chat";
+import { getMessagesPage, saveCompleteMessage } from "@/lib/chat";
 
 interface Message {
   id: string;
@@ -15,6 +15,10 @@ export function useMessageManager(
 ) {
   const [dbMessages, setDbMessages] = useState<any[]>([]);
   const [isLoadingMessages, setIsLoadingMessages] = useState(false);
+  // Cursor of the next older page; null once the whole conversation is loaded
+  const [olderCursor, setOlderCursor] = useState<string | null>(null);
+  const loadedOlderRef = useRef(false);
+  const loadingOlderRef = useRef(false);
   const savedMessageIdsRef = useRef<Set<string>>(new Set());
   const titleGeneratedRef = useRef<boolean>(false);
   const messagesSinceLastTitleUpdateRef = useRef<number>(0);
@@ -24,8 +28,21 @@ export function useMessageManager(
     useState(false);
   const completeTextRef = useRef<string | null>(null);
   const usageDataRef = useRef<any>(null);
+  // Reply the complete text and usage belong to; without one, the last assistant message
+  const finishedMessageIdRef = useRef<string | null>(null);
 
-  // Function to load messages directly from database
+  const isFinishedMessage = (msg: Message, index: number, arr: Message[]) => {
+    if (msg.role !== "assistant") return false;
+    if (finishedMessageIdRef.current) return msg.id === finishedMessageIdRef.current;
+    const lastAssistantIndex = arr
+      .map((m, i) => (m.role === "assistant" ? i : -1))
+      .filter((i) => i !== -1)
+      .pop();
+    return index === lastAssistantIndex;
+  };
+
+  // Function to load the latest messages directly from database;
+  // older pages loaded by scrolling stay in front of them
   const loadMessagesFromDb = async () => {
     if (!convId) {
       setDbMessages([]);
@@ -34,8 +51,15 @@ export function useMessageManager(
 
     try {
       setIsLoadingMessages(true);
-      const existingMessages = await getMessages(convId);
-      setDbMessages(existingMessages);
+      const page = await getMessagesPage(convId);
+      const pageIds = new Set(page.items.map((msg) => msg.id));
+      setDbMessages((current) => [
+        ...current.filter((msg) => !pageIds.has(msg.id)),
+        ...page.items,
+      ]);
+      if (!loadedOlderRef.current) {
+        setOlderCursor(page.older_cursor);
+      }
       setIsLoadingMessages(false);
     } catch (error) {
       console.error("Failed to load messages from database:", error);
@@ -43,6 +67,29 @@ export function useMessageManager(
     }
   };
 
+  // Load the page before the oldest loaded message, e.g. when scrolled to the top
+  const loadOlderMessages = async () => {
+    if (!convId || !olderCursor || loadingOlderRef.current) return;
+
+    loadingOlderRef.current = true;
+    try {
+      const page = await getMessagesPage(convId, { before: olderCursor });
+      loadedOlderRef.current = true;
+      setOlderCursor(page.older_cursor);
+      setDbMessages((current) => {
+        const loadedIds = new Set(current.map((msg) => msg.id));
+        return [
+          ...page.items.filter((msg) => !loadedIds.has(msg.id)),
+          ...current,
+        ];
+      });
+    } catch (error) {
+      console.error("Failed to load older messages:", error);
+    } finally {
+      loadingOlderRef.current = false;
+    }
+  };
+
   // Save new messages as they complete
   useEffect(() => {
     const saveNewMessages = async () => {
@@ -54,12 +101,7 @@ export function useMessageManager(
 
       // Process messages to inject complete text and usage data if available
       const messagesToProcess = newMessages.map((msg, index, arr) => {
-        const lastAssistantIndex = arr.map((m, i) => m.role === "assistant" ? i : -1).filter(i => i !== -1).pop();
-        const isLastAssistantMessage = 
-          msg.role === "assistant" && 
-          index === lastAssistantIndex;
-        
-        if (isLastAssistantMessage) {
+        if (isFinishedMessage(msg, index, arr)) {
           const updatedMsg = { ...msg };
           
           // Inject complete text
@@ -130,13 +172,8 @@ export function useMessageManager(
   
   // Process new messages - inject complete text and usage data if available
   const processedNewMessages = newMessages.map((msg, index, arr) => {
-    // If we have complete text and this is the last assistant message, inject it
-    const lastAssistantIndex = arr.map((m, i) => m.role === "assistant" ? i : -1).filter(i => i !== -1).pop();
-    const isLastAssistantMessage = 
-      msg.role === "assistant" && 
-      index === lastAssistantIndex;
-    
-    if (isLastAssistantMessage) {
+    // If we have complete text and this is the finished assistant message, inject it
+    if (isFinishedMessage(msg, index, arr)) {
       const updatedMsg = { ...msg };
       
       // Inject complete text
@@ -198,21 +235,18 @@ export function useMessageManager(
   useEffect(() => {
     const handleAiResponseFinished = (e: Event) => {
       const customEvent = e as CustomEvent;
-      
-      // Store the complete text and usage data
-      if (customEvent.detail.text) {
-        completeTextRef.current = customEvent.detail.text;
-      }
-      
-      // Store usage data
-      if (customEvent.detail.totalUsage) {
-        usageDataRef.current = customEvent.detail.totalUsage;
-      }
-      
-      // Find the last assistant message and clear its saved status so it can be re-saved
-      const lastAssistantMsg = [...newMessages].reverse().find((msg) => msg.role === "assistant");
-      if (lastAssistantMsg) {
-        savedMessageIdsRef.current.delete(lastAssistantMsg.id);
+
+      // Text and usage of an earlier reply must not end up on this one
+      finishedMessageIdRef.current = customEvent.detail.messageId ?? null;
+      completeTextRef.current = customEvent.detail.text || null;
+      usageDataRef.current = customEvent.detail.totalUsage ?? null;
+
+      // Clear the finished message's saved status so it is saved again with them
+      const finishedMsg = finishedMessageIdRef.current
+        ? newMessages.find((msg) => msg.id === finishedMessageIdRef.current)
+        : [...newMessages].reverse().find((msg) => msg.role === "assistant");
+      if (finishedMsg) {
+        savedMessageIdsRef.current.delete(finishedMsg.id);
       }
       
       // Toggle to trigger re-save of assistant messages
@@ -231,8 +265,11 @@ export function useMessageManager(
     // Load messages when conversation ID changes
     lastConversationIdRef.current = convId;
 
-    // Clear saved message IDs when switching conversations
+    // Clear saved message IDs and loaded pages when switching conversations
     savedMessageIdsRef.current.clear();
+    setDbMessages([]);
+    setOlderCursor(null);
+    loadedOlderRef.current = false;
     titleGeneratedRef.current = false;
     messagesSinceLastTitleUpdateRef.current = 0;
     lastTitleUpdateRef.current = Date.now();
@@ -244,6 +281,8 @@ export function useMessageManager(
     dbMessages,
     allMessages,
     isLoadingMessages,
+    hasOlderMessages: olderCursor !== null,
+    loadOlderMessages,
     savedMessageIdsRef,
     titleGeneratedRef,
     messagesSinceLastTitleUpdateRef,
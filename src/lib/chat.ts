@@ -1,5 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
 import type { UIMessage } from "@ai-sdk/react";
+import type { AppError } from "./errors";
 
 export type Conversation = {
 	id: string;
@@ -7,6 +8,10 @@ export type Conversation = {
 	created_at: string;
 	archived: number;
 	model?: string;
+	updated_at: string; // Newest message time, or created_at when empty
+	message_count: number;
+	last_message_preview: string | null;
+	total_tokens: number;
 };
 
 // Keep the old Message type for backward compatibility during transition
@@ -24,14 +29,75 @@ export type ConversationMessage = UIMessage & {
 	created_at: string;
 };
 
+// Unknown values are null; prices are USD per million tokens
+export type ModelCapabilities = {
+	context_length: number | null;
+	max_output_tokens: number | null;
+	vision: boolean | null;
+	tools: boolean | null;
+	json_mode: boolean | null;
+	reasoning: boolean | null;
+	input_price: number | null;
+	output_price: number | null;
+};
+
 export type ListedModel = {
 	model: string;
 	adapter_kind: string;
 	enabled: boolean;
+	capabilities?: ModelCapabilities; // Only for models listed by the backend
+	error?: AppError | null; // Why the provider's models could not be fetched
 };
 
-export async function listConversations(): Promise<Conversation[]> {
-	return await invoke<Conversation[]>("db_list_conversations");
+export type ChatModels = {
+	models: ListedModel[];
+	unavailable: AppError[]; // Providers left out, each named in `provider`
+};
+
+export type ProviderHealthStatus =
+	| "reachable"
+	| "not_configured"
+	| "auth_failed"
+	| "rate_limited"
+	| "dns_error"
+	| "tls_error"
+	| "unreachable"
+	| "timeout"
+	| "server_error"
+	| "error";
+
+export type ProviderHealth = {
+	adapter_kind: string;
+	base_url: string;
+	status: ProviderHealthStatus;
+	latency_ms: number | null;
+	server_version: string | null;
+	message: string;
+};
+
+// Cursors are opaque; pass older_cursor as `before` and newer_cursor as `after`
+export type Page<T> = {
+	items: T[];
+	older_cursor: string | null;
+	newer_cursor: string | null;
+};
+
+export type PageInput = {
+	limit?: number;
+	before?: string;
+	after?: string;
+};
+
+export type ConversationQuery = PageInput & {
+	archived?: boolean;
+	model?: string;
+};
+
+// Most recently active first
+export async function listConversations(
+	query?: ConversationQuery,
+): Promise<Page<Conversation>> {
+	return await invoke<Page<Conversation>>("db_list_conversations", { query });
 }
 
 // Legacy function - returns old format for backward compatibility
@@ -44,11 +110,15 @@ export async function getLegacyMessages(
 	});
 }
 
-// New function - now works with AI SDK messages natively from backend
-export async function getMessages(conversationId: string): Promise<any[]> {
-	return await invoke<any[]>("db_get_ai_messages", {
+// A page of AI SDK messages, oldest first; without a cursor the latest messages
+export async function getMessagesPage(
+	conversationId: string,
+	page?: PageInput,
+): Promise<Page<any>> {
+	return await invoke<Page<any>>("db_get_ai_messages", {
 		conversationId,
 		conversation_id: conversationId,
+		page,
 	});
 }
 
@@ -102,8 +172,51 @@ export async function streamChat(
 	});
 }
 
-export async function listChatModels(): Promise<ListedModel[]> {
-	return await invoke<ListedModel[]>("list_chat_models");
+// Run a generation in the backend and return its text; nothing is streamed or saved
+export async function completeChat(
+	conversationId: string,
+	model: string,
+	adapterKind: string,
+	messages: { role: "system" | "user" | "assistant"; content: string }[],
+): Promise<string> {
+	return await invoke<string>("complete_provider_chat", {
+		input: {
+			conversation_id: conversationId,
+			adapter_kind: adapterKind,
+			model,
+			messages,
+		},
+	});
+}
+
+export async function listChatModels(): Promise<ChatModels> {
+	return await invoke<ChatModels>("list_chat_models");
+}
+
+export async function getModelCapabilities(
+	model: string,
+	adapterKind?: string,
+): Promise<ModelCapabilities | undefined> {
+	const { models } = await listChatModels();
+	return models.find(
+		(m) =>
+			m.model === model && (!adapterKind || m.adapter_kind === adapterKind),
+	)?.capabilities;
+}
+
+// Re-query providers instead of using the cached lists; all of them when no kind is given
+export async function refreshChatModels(
+	adapterKind?: string,
+): Promise<ChatModels> {
+	return await invoke<ChatModels>("refresh_chat_models", {
+		adapterKind: adapterKind ?? null,
+	});
+}
+
+export async function checkProviderHealth(
+	adapterKind: string,
+): Promise<ProviderHealth> {
+	return await invoke<ProviderHealth>("check_provider_health", { adapterKind });
 }
 
 export async function deleteConversation(id: string): Promise<void> {
@@ -130,3 +130,43 @@ function getStatusCodeByType(type: ErrorType) {
 			return 500;
 	}
 }
+
+// Error returned by Tauri commands and sent with `chat_stream_error`, `chat_stream_cancelled`
+// and `ollama_pull_end`
+export type AppErrorKind =
+	| "database"
+	| "io"
+	| "serialization"
+	| "invalid_input"
+	| "not_found"
+	| "not_configured"
+	| "auth"
+	| "rate_limited"
+	| "network"
+	| "provider"
+	| "cancelled"
+	| "internal";
+
+export type AppError = {
+	kind: AppErrorKind;
+	message: string;
+	retryable: boolean;
+	provider: string | null; // Adapter kind of the provider that failed
+	details: string | null;
+};
+
+export function isAppError(error: unknown): error is AppError {
+	return (
+		typeof error === "object" &&
+		error !== null &&
+		"kind" in error &&
+		typeof (error as AppError).message === "string"
+	);
+}
+
+// Readable text for anything thrown by `invoke` or the AI SDK
+export function errorMessage(error: unknown): string {
+	if (isAppError(error) || error instanceof Error) return error.message;
+	if (typeof error === "string") return error;
+	return JSON.stringify(error);
+}
@@ -1,19 +1,50 @@
-import { generateText } from "ai";
-import { updateConversationTitle } from "./chat";
+import { backendAdapterKind } from "@/components/providers";
+import { completeChat, updateConversationTitle } from "./chat";
+
+// The selected model, which the backend calls by id and adapter kind
+interface TitleModel {
+  modelId: string;
+  adapterKind?: string;
+}
+
 interface GenerateTitleProps {
   chatId: string;
   firstMessage: any;
-  model: any;
+  model: TitleModel;
 }
 
 interface DetectContextChangeProps {
   chatId: string;
   recentMessages: any[];
-  model: any;
+  model: TitleModel;
   currentTitle: string;
   messagesSinceLastUpdate?: number;
 }
 
+// One prompt to the selected model, run by the backend so keys stay out of the webview
+async function generateText({
+  chatId,
+  model,
+  system,
+  prompt,
+}: {
+  chatId: string;
+  model: TitleModel;
+  system: string;
+  prompt: string;
+}): Promise<{ text: string }> {
+  const text = await completeChat(
+    chatId,
+    model.modelId,
+    await backendAdapterKind(model.modelId, model.adapterKind),
+    [
+      { role: "system", content: system },
+      { role: "user", content: prompt },
+    ],
+  );
+  return { text };
+}
+
 export async function generateTitle(props: GenerateTitleProps) {
   const { chatId, firstMessage, model } = props;
 
@@ -34,13 +65,9 @@ export async function generateTitle(props: GenerateTitleProps) {
     }
 
     const { text } = await generateText({
-      model: model,
-      messages: [
-        {
-          role: "user",
-          content: messageText,
-        },
-      ],
+      chatId,
+      model,
+      prompt: messageText,
       system: `Generate a concise, descriptive title (3-8 words) for this chat based on the user's first message. Focus on the main topic or question being asked. Return only the title without quotes or extra formatting.`,
     });
 
@@ -119,11 +146,9 @@ export async function detectContextChange(
 
   try {
     const { text } = await generateText({
+      chatId,
       model,
-      messages: [
-        {
-          role: "user",
-          content: `Current conversation title: "${currentTitle}"
+      prompt: `Current conversation title: "${currentTitle}"
 
 Recent conversation:
 ${conversationText}
@@ -136,8 +161,6 @@ If no, respond with "NO_CHANGE".
 Response format:
 - If context changed: NEW_TITLE: [your suggested title]
 - If context unchanged: NO_CHANGE`,
-        },
-      ],
       system:
         "You are analyzing conversation context changes. Be conservative - only suggest title changes when there's a clear, significant shift in the main topic of discussion.",
     });
@@ -0,0 +1,158 @@
+import { useState } from "react";
+import { ArrowLeft } from "lucide-react";
+import { errorMessage } from "@/lib/errors";
+
+export type CustomProvider = {
+	name: string;
+	base_url: string;
+	api_key?: string | null;
+	headers?: Record<string, string> | null;
+	models?: string[] | null;
+};
+
+// Headers are edited as one "Name: value" pair per line
+function headersToText(headers?: Record<string, string> | null): string {
+	return Object.entries(headers ?? {})
+		.map(([name, value]) => `${name}: ${value}`)
+		.join("\n");
+}
+
+function textToHeaders(text: string): Record<string, string> | null {
+	const headers: Record<string, string> = {};
+	for (const line of text.split("\n")) {
+		const at = line.indexOf(":");
+		if (at <= 0) continue;
+		headers[line.slice(0, at).trim()] = line.slice(at + 1).trim();
+	}
+	return Object.keys(headers).length > 0 ? headers : null;
+}
+
+function textToModels(text: string): string[] | null {
+	const models = text
+		.split(/[\n,]/)
+		.map((m) => m.trim())
+		.filter(Boolean);
+	return models.length > 0 ? Array.from(new Set(models)) : null;
+}
+
+export default function CustomProviderForm({
+	initial,
+	onSave,
+	onCancel,
+}: {
+	initial?: CustomProvider;
+	onSave: (provider: CustomProvider) => Promise<void>;
+	onCancel: () => void;
+}) {
+	const [name, setName] = useState(initial?.name ?? "");
+	const [baseUrl, setBaseUrl] = useState(initial?.base_url ?? "");
+	const [apiKey, setApiKey] = useState(initial?.api_key ?? "");
+	const [headers, setHeaders] = useState(headersToText(initial?.headers));
+	const [models, setModels] = useState((initial?.models ?? []).join("\n"));
+	const [error, setError] = useState<string | null>(null);
+	const [isSaving, setIsSaving] = useState(false);
+
+	const save = async () => {
+		setIsSaving(true);
+		setError(null);
+		try {
+			await onSave({
+				name: name.trim(),
+				base_url: baseUrl.trim(),
+				api_key: apiKey.trim() || null,
+				headers: textToHeaders(headers),
+				models: textToModels(models),
+			});
+		} catch (e) {
+			setError(errorMessage(e));
+		} finally {
+			setIsSaving(false);
+		}
+	};
+
+	const inputClass =
+		"w-full bg-white/5 rounded px-3 py-2 text-sm border border-white/10";
+
+	return (
+		<div className="flex-1 space-y-4">
+			<button
+				className="inline-flex items-center gap-1 text-xs opacity-70 hover:opacity-100"
+				onClick={onCancel}
+			>
+				<ArrowLeft size={14} />
+			</button>
+			<div className="rounded-lg border border-white/10 bg-white/5 p-4 space-y-3">
+				<div className="text-xl font-mondwest">
+					{initial ? initial.name : "Custom Provider"}
+				</div>
+				<div className="text-xs opacity-70">
+					Any server with an OpenAI-compatible API, such as vLLM, LM Studio
+					or an internal gateway.
+				</div>
+				<div>
+					<div className="text-sm mb-1">Name</div>
+					<input
+						className={inputClass}
+						placeholder="My Gateway"
+						value={name}
+						onChange={(e) => setName(e.target.value)}
+					/>
+				</div>
+				<div>
+					<div className="text-sm mb-1">Base URL</div>
+					<input
+						className={inputClass}
+						placeholder="http://localhost:8000/v1"
+						value={baseUrl}
+						onChange={(e) => setBaseUrl(e.target.value)}
+					/>
+				</div>
+				<div>
+					<div className="text-sm mb-1">API Key (optional)</div>
+					<input
+						type="password"
+						className={inputClass}
+						value={apiKey}
+						onChange={(e) => setApiKey(e.target.value)}
+					/>
+				</div>
+				<div>
+					<div className="text-sm mb-1">Headers (optional)</div>
+					<textarea
+						className={`${inputClass} font-mono`}
+						rows={3}
+						placeholder="X-Team: research"
+						value={headers}
+						onChange={(e) => setHeaders(e.target.value)}
+					/>
+				</div>
+				<div>
+					<div className="text-sm mb-1">Models</div>
+					<textarea
+						className={`${inputClass} font-mono`}
+						rows={4}
+						placeholder="One per line; leave empty to discover them from /models"
+						value={models}
+						onChange={(e) => setModels(e.target.value)}
+					/>
+				</div>
+				{error && <div className="text-xs text-red-400">{error}</div>}
+				<div className="flex justify-end gap-2">
+					<button
+						className="px-3 py-2 text-sm rounded bg-white/10 border border-white/20 hover:bg-white/15"
+						onClick={onCancel}
+					>
+						Cancel
+					</button>
+					<button
+						className="px-3 py-2 text-sm rounded bg-blue-500/20 border border-blue-500/30 hover:bg-blue-500/30 text-blue-200 disabled:opacity-50 disabled:cursor-not-allowed"
+						onClick={save}
+						disabled={isSaving || !name.trim() || !baseUrl.trim()}
+					>
+						Save
+					</button>
+				</div>
+			</div>
+		</div>
+	);
+}
@@ -9,8 +9,14 @@ import {
 	Trash,
 	Settings as SettingsIcon,
 	ArrowLeft,
+	Server,
+	Activity,
 } from "lucide-react";
 import ModelIcon, { type ModelType } from "@/components/model-icon";
+import { checkProviderHealth, type ProviderHealth } from "@/lib/chat";
+import { setApiKey } from "@/lib/api-keys";
+import { errorMessage } from "@/lib/errors";
+import CustomProviderForm, { type CustomProvider } from "./_CustomProviderForm";
 
 type Settings = {
 	openai_api_key?: string | null;
@@ -19,24 +25,33 @@ type Settings = {
 	groq_api_key?: string | null;
 	openrouter_api_key?: string | null;
 	deepseek_api_key?: string | null;
+	mistral_api_key?: string | null;
+	xai_api_key?: string | null;
 	openai_base_url?: string | null;
 	anthropic_base_url?: string | null;
 	gemini_base_url?: string | null;
 	groq_base_url?: string | null;
 	openrouter_base_url?: string | null;
 	deepseek_base_url?: string | null;
+	mistral_base_url?: string | null;
+	xai_base_url?: string | null;
 	openai_models?: string[] | null;
 	anthropic_models?: string[] | null;
 	gemini_models?: string[] | null;
 	groq_models?: string[] | null;
 	openrouter_models?: string[] | null;
 	deepseek_models?: string[] | null;
+	mistral_models?: string[] | null;
+	xai_models?: string[] | null;
 	openai_enabled?: boolean | null;
 	anthropic_enabled?: boolean | null;
 	gemini_enabled?: boolean | null;
 	groq_enabled?: boolean | null;
 	openrouter_enabled?: boolean | null;
 	deepseek_enabled?: boolean | null;
+	mistral_enabled?: boolean | null;
+	xai_enabled?: boolean | null;
+	custom_providers?: CustomProvider[] | null;
 };
 
 type ProviderKey =
@@ -45,7 +60,9 @@ type ProviderKey =
 	| "Gemini"
 	| "Groq"
 	| "OpenRouter"
-	| "DeepSeek";
+	| "DeepSeek"
+	| "Mistral"
+	| "X.AI";
 
 const providerMeta: Record<
 	ProviderKey,
@@ -53,6 +70,7 @@ const providerMeta: Record<
 		keyField: keyof Settings;
 		baseUrlField: keyof Settings;
 		modelsField: keyof Settings;
+		enabledField: keyof Settings;
 		placeholder: string;
 		docs: string;
 	}
@@ -61,6 +79,7 @@ const providerMeta: Record<
 		keyField: "openai_api_key",
 		baseUrlField: "openai_base_url",
 		modelsField: "openai_models",
+		enabledField: "openai_enabled",
 		placeholder: "sk-...",
 		docs: "https://platform.openai.com/docs/overview",
 	},
@@ -68,6 +87,7 @@ const providerMeta: Record<
 		keyField: "anthropic_api_key",
 		baseUrlField: "anthropic_base_url",
 		modelsField: "anthropic_models",
+		enabledField: "anthropic_enabled",
 		placeholder: "sk-ant-...",
 		docs: "https://docs.anthropic.com/",
 	},
@@ -75,6 +95,7 @@ const providerMeta: Record<
 		keyField: "gemini_api_key",
 		baseUrlField: "gemini_base_url",
 		modelsField: "gemini_models",
+		enabledField: "gemini_enabled",
 		placeholder: "AIza...",
 		docs: "https://ai.google.dev/gemini-api/docs",
 	},
@@ -82,6 +103,7 @@ const providerMeta: Record<
 		keyField: "groq_api_key",
 		baseUrlField: "groq_base_url",
 		modelsField: "groq_models",
+		enabledField: "groq_enabled",
 		placeholder: "gsk_...",
 		docs: "https://console.groq.com/docs",
 	},
@@ -89,6 +111,7 @@ const providerMeta: Record<
 		keyField: "openrouter_api_key",
 		baseUrlField: "openrouter_base_url",
 		modelsField: "openrouter_models",
+		enabledField: "openrouter_enabled",
 		placeholder: "or-...",
 		docs: "https://openrouter.ai/docs",
 	},
@@ -96,20 +119,63 @@ const providerMeta: Record<
 		keyField: "deepseek_api_key",
 		baseUrlField: "deepseek_base_url",
 		modelsField: "deepseek_models",
+		enabledField: "deepseek_enabled",
 		placeholder: "sk-...",
 		docs: "https://platform.deepseek.com/docs",
 	},
+	Mistral: {
+		keyField: "mistral_api_key",
+		baseUrlField: "mistral_base_url",
+		modelsField: "mistral_models",
+		enabledField: "mistral_enabled",
+		placeholder: "...",
+		docs: "https://docs.mistral.ai/",
+	},
+	"X.AI": {
+		keyField: "xai_api_key",
+		baseUrlField: "xai_base_url",
+		modelsField: "xai_models",
+		enabledField: "xai_enabled",
+		placeholder: "xai-...",
+		docs: "https://docs.x.ai/",
+	},
 };
 
+function HealthDot({ health }: { health?: ProviderHealth }) {
+	const color = !health
+		? "bg-white/20 animate-pulse"
+		: health.status === "reachable"
+			? "bg-green-500"
+			: health.status === "not_configured"
+				? "bg-white/30"
+				: health.status === "rate_limited"
+					? "bg-yellow-500"
+					: "bg-red-500";
+	return (
+		<span
+			className={`inline-block size-2 rounded-full ${color}`}
+			title={health?.message ?? "Checking..."}
+		/>
+	);
+}
+
 export default function ProvidersTab() {
 	const [active, setActive] = useState<ProviderKey | null>(null);
 	const [settings, setSettings] = useState<Settings>({});
 	const [showKey, setShowKey] = useState(false);
+	// Stored keys come back redacted, so the field only holds a key being typed
+	const [keyDraft, setKeyDraft] = useState("");
 	const [fetchedModels, setFetchedModels] = useState<string[]>([]);
 	const [isFetchingModels, setIsFetchingModels] = useState(false);
 	const [fetchError, setFetchError] = useState<string | null>(null);
 	const [customModelInput, setCustomModelInput] = useState("");
 	const [showCustomInput, setShowCustomInput] = useState(false);
+	// Index of the custom provider being edited, or "new" while adding one
+	const [editingCustom, setEditingCustom] = useState<number | "new" | null>(
+		null,
+	);
+	const [health, setHealth] = useState<Record<string, ProviderHealth>>({});
+	const [isCheckingHealth, setIsCheckingHealth] = useState(false);
 
 	useEffect(() => {
 		(async () => {
@@ -120,7 +186,23 @@ export default function ProvidersTab() {
 		})();
 	}, []);
 
+	// Check every provider whenever the list is shown
+	const customNames = (settings.custom_providers ?? [])
+		.map((p) => p.name)
+		.join("\n");
 	useEffect(() => {
+		if (active !== null || editingCustom !== null) return;
+		const kinds = [
+			...Object.keys(providerMeta),
+			...customNames.split("\n").filter(Boolean),
+		];
+		for (const kind of kinds) {
+			void checkHealth(kind);
+		}
+	}, [active, editingCustom, customNames]);
+
+	useEffect(() => {
+		setKeyDraft("");
 		if (active) {
 			fetchAdapterModels(active);
 		} else {
@@ -134,6 +216,14 @@ export default function ProvidersTab() {
 			const saved = await invoke<any>("update_settings", { update: patch });
 			setSettings(saved);
 
+			// A new key or URL makes the last check meaningless
+			if (active) {
+				const meta = providerMeta[active];
+				if (meta.keyField in patch || meta.baseUrlField in patch) {
+					setHealth(({ [active]: _, ...rest }) => rest);
+				}
+			}
+
 			// Auto-load models if an API key was just set
 			if (active) {
 				const meta = providerMeta[active];
@@ -168,6 +258,51 @@ export default function ProvidersTab() {
 		} catch {}
 	};
 
+	const saveKey = async (value: string) => {
+		if (!active) return;
+		setKeyDraft(value);
+		const keyField = providerMeta[active].keyField;
+		if (value.trim()) {
+			await update({ [keyField]: value } as any);
+			return;
+		}
+		// Saving settings never clears a key, so an emptied field removes it directly
+		try {
+			await setApiKey(keyField.replace("_api_key", ""), "");
+			setSettings(await invoke<any>("get_settings"));
+			setHealth(({ [active]: _, ...rest }) => rest);
+		} catch {}
+	};
+
+	const checkHealth = async (adapterKind: string) => {
+		try {
+			const result = await checkProviderHealth(adapterKind);
+			setHealth((prev) => ({ ...prev, [adapterKind]: result }));
+		} catch (error) {
+			// The provider could not be built from settings
+			setHealth((prev) => ({
+				...prev,
+				[adapterKind]: {
+					adapter_kind: adapterKind,
+					base_url: "",
+					status: "error",
+					latency_ms: null,
+					server_version: null,
+					message: errorMessage(error),
+				},
+			}));
+		}
+	};
+
+	const testConnection = async (adapterKind: string) => {
+		setIsCheckingHealth(true);
+		try {
+			await checkHealth(adapterKind);
+		} finally {
+			setIsCheckingHealth(false);
+		}
+	};
+
 	const fetchAdapterModels = async (adapterKind: ProviderKey) => {
 		setIsFetchingModels(true);
 		setFetchError(null);
@@ -177,7 +312,7 @@ export default function ProvidersTab() {
 			});
 			setFetchedModels(models);
 		} catch (error) {
-			setFetchError(error as string);
+			setFetchError(errorMessage(error));
 			setFetchedModels([]);
 		} finally {
 			setIsFetchingModels(false);
@@ -246,6 +381,31 @@ export default function ProvidersTab() {
 		setShowCustomInput(false);
 	};
 
+	const customProviders = settings.custom_providers ?? [];
+
+	// Unlike `update`, errors are passed on so the form can show why a save was rejected
+	const saveCustomProviders = async (list: CustomProvider[]) => {
+		const saved = await invoke<any>("update_settings", {
+			update: { custom_providers: list },
+		});
+		setSettings(saved);
+	};
+
+	const saveCustomProvider = async (provider: CustomProvider) => {
+		const list =
+			editingCustom === "new" || editingCustom === null
+				? [...customProviders, provider]
+				: customProviders.map((p, i) => (i === editingCustom ? provider : p));
+		await saveCustomProviders(list);
+		setEditingCustom(null);
+	};
+
+	const deleteCustomProvider = (index: number) => {
+		const name = customProviders[index]?.name;
+		if (!confirm(`Remove ${name}?`)) return;
+		void saveCustomProviders(customProviders.filter((_, i) => i !== index));
+	};
+
 	const providerToIcon = (p: ProviderKey): ModelType => {
 		switch (p) {
 			case "OpenAI":
@@ -260,6 +420,10 @@ export default function ProvidersTab() {
 				return "openrouter";
 			case "DeepSeek":
 				return "deepseek";
+			case "Mistral":
+				return "mistral";
+			case "X.AI":
+				return "xai";
 			default:
 				return "openai";
 		}
@@ -267,13 +431,21 @@ export default function ProvidersTab() {
 
 	return (
 		<div className="flex w-full gap-4">
-			{active === null ? (
+			{editingCustom !== null ? (
+				<CustomProviderForm
+					initial={
+						editingCustom === "new" ? undefined : customProviders[editingCustom]
+					}
+					onSave={saveCustomProvider}
+					onCancel={() => setEditingCustom(null)}
+				/>
+			) : active === null ? (
 				<div className="w-full">
 					<div className="flex items-center justify-between mb-2">
 						<div className="text-lg font-mondwest">Model Providers</div>
 						<button
 							className="px-2 py-1 text-xs rounded-md bg-white/10 border border-white/10 inline-flex items-center gap-1"
-							disabled
+							onClick={() => setEditingCustom("new")}
 						>
 							+ Add Provider
 						</button>
@@ -287,10 +459,11 @@ export default function ProvidersTab() {
 								"Groq",
 								"OpenRouter",
 								"DeepSeek",
+								"Mistral",
+								"X.AI",
 							] as ProviderKey[]
 						).map((p) => {
-							const enabledField =
-								`${p.toLowerCase()}_enabled` as keyof Settings;
+							const enabledField = providerMeta[p].enabledField;
 							const modelsField = providerMeta[p].modelsField;
 							const modelCount = (
 								(settings[modelsField] as string[] | null) ?? []
@@ -305,7 +478,10 @@ export default function ProvidersTab() {
 									<div className="flex items-center gap-4">
 										<ModelIcon className="size-7" model={providerToIcon(p)} />
 										<div className="flex flex-col">
-											<div className="font-medium">{p}</div>
+											<div className="font-medium flex items-center gap-2">
+												{p}
+												{isEnabled && <HealthDot health={health[p]} />}
+											</div>
 											<div className="text-xs opacity-70">
 												{modelCount} Models
 											</div>
@@ -334,6 +510,44 @@ export default function ProvidersTab() {
 								</div>
 							);
 						})}
+						{customProviders.map((p, index) => (
+							<div
+								key={p.name}
+								className="flex items-center justify-between px-3 py-3 hover:bg-white/5"
+							>
+								<div className="flex items-center gap-4">
+									<Server className="size-7 p-1" />
+									<div className="flex flex-col">
+										<div className="font-medium flex items-center gap-2">
+											{p.name}
+											<HealthDot health={health[p.name]} />
+										</div>
+										<div className="text-xs opacity-70">
+											{p.models?.length
+												? `${p.models.length} Models`
+												: "Discovers models"}{" "}
+											· {p.base_url}
+										</div>
+									</div>
+								</div>
+								<div className="flex items-center gap-2">
+									<button
+										className="px-2 py-1 text-xs rounded-md bg-white/10 border border-white/10"
+										title="Edit"
+										onClick={() => setEditingCustom(index)}
+									>
+										<Pencil size={14} />
+									</button>
+									<button
+										className="px-2 py-1 text-xs rounded-md bg-white/10 border border-white/10"
+										title="Delete"
+										onClick={() => deleteCustomProvider(index)}
+									>
+										<Trash size={14} />
+									</button>
+								</div>
+							</div>
+						))}
 					</div>
 				</div>
 			) : (
@@ -366,15 +580,13 @@ export default function ProvidersTab() {
 								type={showKey ? "text" : "password"}
 								name="key"
 								className="w-full bg-white/5 rounded px-3 py-2 text-sm border border-white/10"
-								placeholder={providerMeta[active].placeholder}
-								value={
-									(settings[providerMeta[active].keyField] as string) ?? ""
-								}
-								onChange={(e) =>
-									update({
-										[providerMeta[active].keyField]: e.target.value,
-									} as any)
+								placeholder={
+									hasApiKey
+										? "Key saved; type to replace it"
+										: providerMeta[active].placeholder
 								}
+								value={keyDraft}
+								onChange={(e) => void saveKey(e.target.value)}
 							/>
 							<button
 								className="px-2 py-1 text-xs rounded-md bg-white/10 border border-white/10"
@@ -385,9 +597,7 @@ export default function ProvidersTab() {
 							<button
 								className="px-2 py-1 text-xs rounded-md bg-white/10 border border-white/10"
 								onClick={() => {
-									const v =
-										(settings[providerMeta[active].keyField] as string) ?? "";
-									if (v) navigator.clipboard.writeText(v);
+									if (keyDraft) navigator.clipboard.writeText(keyDraft);
 								}}
 							>
 								<Copy size={16} />
@@ -409,6 +619,43 @@ export default function ProvidersTab() {
 								}
 							/>
 						</div>
+						<div className="mt-3 flex items-start gap-2">
+							<button
+								className="inline-flex shrink-0 items-center gap-1 px-2 py-1 text-xs rounded-md bg-white/10 border border-white/20 hover:bg-white/15 disabled:opacity-50 disabled:cursor-not-allowed"
+								onClick={() => testConnection(active)}
+								disabled={isCheckingHealth}
+							>
+								<Activity size={14} />
+								{isCheckingHealth ? "Testing..." : "Test Connection"}
+							</button>
+							{health[active] && (
+								<div className="flex items-start gap-2 pt-1 text-xs">
+									<span className="pt-1">
+										<HealthDot health={health[active]} />
+									</span>
+									<div>
+										<div
+											className={
+												health[active].status === "reachable"
+													? "text-green-400"
+													: "text-red-400"
+											}
+										>
+											{health[active].message}
+										</div>
+										{(health[active].latency_ms !== null ||
+											health[active].server_version) && (
+											<div className="opacity-60">
+												{health[active].latency_ms !== null &&
+													`${health[active].latency_ms} ms`}
+												{health[active].server_version &&
+													` · version ${health[active].server_version}`}
+											</div>
+										)}
+									</div>
+								</div>
+							)}
+						</div>
 					</div>
 
 					<div className="rounded-lg border border-white/10 bg-white/5">